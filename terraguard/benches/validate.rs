@@ -0,0 +1,93 @@
+//! Validation throughput on synthetic plans. Run with `cargo bench`; pass a
+//! filter such as `cargo bench -- plan/10000` to run a single size.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::hint::black_box;
+use terraguard::rules::{fast_validate, FastValidator};
+use terraguard::types::Plan;
+
+const SIZES: [usize; 2] = [1_000, 10_000];
+
+/// A plan creating `count` resources: security groups with open and
+/// restricted rules, standalone rules, instances and buckets, in equal parts
+fn synthetic_plan(count: usize) -> Value {
+    let changes: Vec<Value> = (0..count).map(|i| {
+        let (resource_type, after) = match i % 4 {
+            0 => ("aws_security_group", json!({
+                "id": format!("sg-{:08x}", i),
+                "tags": { "Name": format!("sg-{}", i) },
+                "ingress": [
+                    { "protocol": "tcp", "from_port": 22, "to_port": 22, "cidr_blocks": ["0.0.0.0/0"] },
+                    { "protocol": "tcp", "from_port": 443, "to_port": 443, "cidr_blocks": ["10.0.0.0/8"] },
+                    { "protocol": "-1", "from_port": 0, "to_port": 0, "ipv6_cidr_blocks": ["::/0"] }
+                ],
+                "egress": [{ "protocol": "-1", "from_port": 0, "to_port": 0, "cidr_blocks": ["0.0.0.0/0"] }]
+            })),
+            1 => ("aws_security_group_rule", json!({
+                "type": "ingress",
+                "security_group_id": format!("sg-{:08x}", i - 1),
+                "protocol": "tcp",
+                "from_port": 3306,
+                "to_port": 3306,
+                "cidr_blocks": ["172.16.0.0/12"]
+            })),
+            2 => ("aws_instance", json!({
+                "associate_public_ip_address": i % 8 == 2,
+                "metadata_options": [{ "http_tokens": "optional" }]
+            })),
+            _ => ("aws_s3_bucket", json!({
+                "acl": "private",
+                "versioning": [{ "enabled": false }]
+            })),
+        };
+        let name = format!("r{}", i);
+        json!({
+            "address": format!("module.unit{}.{}.{}", i % 16, resource_type, name),
+            "module_address": format!("module.unit{}", i % 16),
+            "mode": "managed",
+            "type": resource_type,
+            "name": name,
+            "change": { "actions": ["create"], "before": null, "after": after, "after_unknown": {} }
+        })
+    }).collect();
+
+    json!({ "format_version": "1.2", "terraform_version": "1.9.0", "resource_changes": changes })
+}
+
+fn bench_validate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("plan");
+    group.sample_size(10);
+
+    for size in SIZES {
+        let plan = synthetic_plan(size);
+        let typed = Plan::deserialize(&plan).expect("synthetic plan matches the plan format");
+        group.throughput(Throughput::Elements(size as u64));
+
+        // JSON to findings, as `tg validate` does it
+        group.bench_with_input(BenchmarkId::new("validate", size), &plan, |b, plan| {
+            b.iter(|| FastValidator::new().validate(black_box(plan)))
+        });
+
+        // Checks only, on an already parsed plan
+        group.bench_with_input(BenchmarkId::new("validate_plan", size), &typed, |b, typed| {
+            b.iter(|| FastValidator::new().validate_plan(black_box(typed)))
+        });
+
+        // The same work on a single thread, for the parallel speedup
+        let single = rayon::ThreadPoolBuilder::new().num_threads(1).build().expect("thread pool");
+        group.bench_with_input(BenchmarkId::new("validate_plan_1_thread", size), &typed, |b, typed| {
+            b.iter(|| single.install(|| FastValidator::new().validate_plan(black_box(typed))))
+        });
+    }
+    group.finish();
+}
+
+fn bench_fast_validate(c: &mut Criterion) {
+    let plan = synthetic_plan(100);
+    c.bench_function("fast_validate/100", |b| b.iter(|| fast_validate(black_box(&plan))));
+}
+
+criterion_group!(benches, bench_validate, bench_fast_validate);
+criterion_main!(benches);
@@ -0,0 +1,211 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::Path;
+use crate::types::Issue;
+
+/// Default file written by `tg baseline create`
+pub const DEFAULT_BASELINE_FILE: &str = "terraguard-baseline.json";
+
+/// Findings accepted as existing debt. Later runs only report findings whose
+/// fingerprint is not part of the baseline.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Baseline {
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    pub entries: Vec<BaselineEntry>,
+}
+
+/// A single baselined finding. Only the fingerprint and the source are used
+/// for matching; the other fields make the file reviewable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    pub fingerprint: String,
+    // The input the finding came from, relative to the working directory. Units
+    // of a Terragrunt stack often share addresses such as `aws_security_group.this`,
+    // so an entry only covers its own unit. Entries without a source match any input.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub rule_id: String,
+    pub resource: String,
+    pub message: String,
+}
+
+/// Result of filtering issues through a baseline
+#[derive(Debug, Default)]
+pub struct BaselineOutcome {
+    // Findings missing from the baseline
+    pub new_issues: Vec<Issue>,
+    // Number of findings hidden because they are baselined
+    pub baselined: usize,
+    // Baseline entries that no longer match any finding and can be removed
+    pub stale: Vec<BaselineEntry>,
+}
+
+impl Baseline {
+    /// Build a baseline covering every given issue
+    pub fn from_issues(issues: &[Issue]) -> Self {
+        let mut seen = HashSet::new();
+        let entries = issues.iter()
+            .filter(|issue| seen.insert(scoped_fingerprint(issue)))
+            .map(|issue| BaselineEntry {
+                fingerprint: issue.fingerprint(),
+                source: issue.source.as_deref().map(normalized_source),
+                rule_id: issue.rule_id.clone(),
+                resource: issue.resource.clone(),
+                message: issue.message.clone(),
+            })
+            .collect();
+
+        Self {
+            version: 1,
+            created_at: Utc::now(),
+            entries,
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("could not read baseline file {}: {}", path, e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("invalid baseline file {}: {}", path, e))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("could not serialize baseline: {}", e))?;
+        fs::write(path, content + "\n")
+            .map_err(|e| format!("could not write baseline file {}: {}", path, e))
+    }
+
+    /// Drop baselined findings and collect entries that no longer match anything
+    pub fn apply(&self, issues: Vec<Issue>) -> BaselineOutcome {
+        let scoped: HashSet<(&str, &str)> = self.entries.iter()
+            .filter_map(|e| e.source.as_deref().map(|source| (source, e.fingerprint.as_str())))
+            .collect();
+        let unscoped: HashSet<&str> = self.entries.iter()
+            .filter(|e| e.source.is_none())
+            .map(|e| e.fingerprint.as_str())
+            .collect();
+        let current: HashSet<(Option<String>, String)> = issues.iter().map(scoped_fingerprint).collect();
+        let current_fingerprints: HashSet<&str> = current.iter().map(|(_, fingerprint)| fingerprint.as_str()).collect();
+
+        let mut outcome = BaselineOutcome::default();
+        for issue in issues {
+            let (source, fingerprint) = scoped_fingerprint(&issue);
+            let known = unscoped.contains(fingerprint.as_str())
+                || source.is_some_and(|source| scoped.contains(&(source.as_str(), fingerprint.as_str())));
+            if known {
+                outcome.baselined += 1;
+            } else {
+                outcome.new_issues.push(issue);
+            }
+        }
+
+        outcome.stale = self.entries.iter()
+            .filter(|e| match &e.source {
+                Some(source) => !current.contains(&(Some(source.clone()), e.fingerprint.clone())),
+                None => !current_fingerprints.contains(e.fingerprint.as_str()),
+            })
+            .cloned()
+            .collect();
+
+        outcome
+    }
+}
+
+/// The normalized source and fingerprint a baseline entry is matched on
+fn scoped_fingerprint(issue: &Issue) -> (Option<String>, String) {
+    (issue.source.as_deref().map(normalized_source), issue.fingerprint())
+}
+
+/// An input path relative to the working directory with `/` separators, so
+/// `./live/app/plan.json` and an absolute path to it record the same source
+fn normalized_source(source: &str) -> String {
+    let path = Path::new(source);
+    let relative = env::current_dir().ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok())
+        .unwrap_or(path);
+    relative.to_string_lossy().replace('\\', "/").trim_start_matches("./").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::SG_OPEN_INGRESS_V4;
+    use crate::types::Severity;
+
+    fn issue(resource: &str, source: &str) -> Issue {
+        Issue::new(&SG_OPEN_INGRESS_V4, resource.to_string(), "message".to_string(), Severity::High)
+            .with_attribute("ports", "22")
+            .with_source(source)
+    }
+
+    #[test]
+    fn test_from_issues_deduplicates() {
+        let reworded = Issue { message: "reworded".to_string(), ..issue("aws_security_group.web", "plan.json") };
+        let baseline = Baseline::from_issues(&[
+            issue("aws_security_group.web", "plan.json"),
+            reworded,
+            issue("aws_security_group.db", "plan.json"),
+        ]);
+
+        assert_eq!(baseline.version, 1);
+        assert_eq!(baseline.entries.len(), 2);
+        assert_eq!(baseline.entries[0].resource, "aws_security_group.web");
+        assert_eq!(baseline.entries[0].message, "message");
+        assert_eq!(baseline.entries[1].resource, "aws_security_group.db");
+    }
+
+    #[test]
+    fn test_apply_reports_new_findings_and_stale_entries() {
+        let baseline = Baseline::from_issues(&[
+            issue("aws_security_group.web", "plan.json"),
+            issue("aws_security_group.db", "plan.json"),
+        ]);
+
+        // web is still there, db was fixed and cache is new
+        let outcome = baseline.apply(vec![
+            issue("aws_security_group.web", "plan.json"),
+            issue("aws_security_group.web", "plan.json"),
+            issue("aws_security_group.cache", "plan.json"),
+        ]);
+        assert_eq!(outcome.baselined, 2);
+        assert_eq!(outcome.new_issues.len(), 1);
+        assert_eq!(outcome.new_issues[0].resource, "aws_security_group.cache");
+        assert_eq!(outcome.stale.len(), 1);
+        assert_eq!(outcome.stale[0].resource, "aws_security_group.db");
+
+        // Another port is another finding
+        let outcome = baseline.apply(vec![issue("aws_security_group.web", "plan.json").with_attribute("ports", "3389")]);
+        assert_eq!(outcome.new_issues.len(), 1);
+        assert_eq!(outcome.stale.len(), 2);
+    }
+
+    #[test]
+    fn test_entries_are_scoped_to_their_source() {
+        let baseline = Baseline::from_issues(&[issue("aws_security_group.this", "./live/app/plan.json")]);
+        assert_eq!(baseline.entries[0].source.as_deref(), Some("live/app/plan.json"));
+
+        // The same address in another Terragrunt unit is a new finding
+        let outcome = baseline.apply(vec![
+            issue("aws_security_group.this", "live/app/plan.json"),
+            issue("aws_security_group.this", "live/db/plan.json"),
+        ]);
+        assert_eq!(outcome.baselined, 1);
+        assert_eq!(outcome.new_issues.len(), 1);
+        assert_eq!(outcome.new_issues[0].source.as_deref(), Some("live/db/plan.json"));
+        assert!(outcome.stale.is_empty());
+
+        // ...and does not keep the entry of its namesake alive
+        let outcome = baseline.apply(vec![issue("aws_security_group.this", "live/db/plan.json")]);
+        assert_eq!(outcome.stale.len(), 1);
+
+        // Entries written without a source match every input
+        let mut unscoped = baseline;
+        unscoped.entries[0].source = None;
+        assert_eq!(unscoped.apply(vec![issue("aws_security_group.this", "live/db/plan.json")]).baselined, 1);
+    }
+}
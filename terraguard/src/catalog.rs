@@ -0,0 +1,148 @@
+/// Static metadata describing a built-in rule. Every issue produced by the
+/// built-in checks references one of these through its `rule_id`.
+#[derive(Debug)]
+pub struct RuleInfo {
+    pub id: &'static str,
+    pub title: &'static str,
+    pub remediation: &'static str,
+    pub doc_url: Option<&'static str>,
+    pub compliance: &'static [&'static str],
+}
+
+const SG_DOCS: Option<&str> = Some("https://docs.aws.amazon.com/vpc/latest/userguide/vpc-security-groups.html");
+
+pub const SG_OPEN_INGRESS_V4: RuleInfo = RuleInfo {
+    id: "TG-SG-001",
+    title: "Security group allows ingress from the internet (IPv4)",
+    remediation: "Restrict cidr_blocks to known address ranges or place the service behind a load balancer",
+    doc_url: SG_DOCS,
+    compliance: &["CIS AWS 5.2", "AWS FSBP EC2.18"],
+};
+
+pub const SG_OPEN_INGRESS_V6: RuleInfo = RuleInfo {
+    id: "TG-SG-002",
+    title: "Security group allows ingress from the internet (IPv6)",
+    remediation: "Restrict ipv6_cidr_blocks to known address ranges",
+    doc_url: SG_DOCS,
+    compliance: &["CIS AWS 5.3", "AWS FSBP EC2.18"],
+};
+
+pub const SG_DISALLOWED_PORT: RuleInfo = RuleInfo {
+    id: "TG-SG-003",
+    title: "Security group allows inbound traffic on a disallowed port",
+    remediation: "Remove the ingress rule or reach the service through a bastion, VPN or SSM Session Manager",
+    doc_url: SG_DOCS,
+    compliance: &["AWS FSBP EC2.19"],
+};
+
+pub const SG_PORT_NOT_ALLOWED: RuleInfo = RuleInfo {
+    id: "TG-SG-004",
+    title: "Security group allows inbound traffic on a port that is not explicitly allowed",
+    remediation: "Add the port to the allowed list if it is intended, otherwise remove the ingress rule",
+    doc_url: SG_DOCS,
+    compliance: &[],
+};
+
+pub const SG_WIDE_INGRESS_RANGE: RuleInfo = RuleInfo {
+    id: "TG-SG-005",
+    title: "Security group allows an overly permissive inbound port range",
+    remediation: "Narrow from_port/to_port to the ports the service actually listens on",
+    doc_url: SG_DOCS,
+    compliance: &[],
+};
+
+pub const SG_UNRESTRICTED_EGRESS: RuleInfo = RuleInfo {
+    id: "TG-SG-006",
+    title: "Security group allows unrestricted egress",
+    remediation: "Limit egress to the destinations and ports the workload needs",
+    doc_url: SG_DOCS,
+    compliance: &[],
+};
+
+pub const SG_WIDE_EGRESS_RANGE: RuleInfo = RuleInfo {
+    id: "TG-SG-007",
+    title: "Security group allows an overly permissive outbound port range",
+    remediation: "Narrow the egress port range to the ports the workload needs",
+    doc_url: SG_DOCS,
+    compliance: &[],
+};
+
+pub const SG_MISSING_TAGS: RuleInfo = RuleInfo {
+    id: "TG-SG-008",
+    title: "Security group is missing tags",
+    remediation: "Add tags identifying the owner and purpose of the security group",
+    doc_url: None,
+    compliance: &[],
+};
+
+pub const SG_MALFORMED_CIDR: RuleInfo = RuleInfo {
+    id: "TG-SG-009",
+    title: "Security group rule contains a malformed CIDR",
+    remediation: "Fix the CIDR so it is a valid IPv4 or IPv6 network such as 10.0.0.0/16",
+    doc_url: SG_DOCS,
+    compliance: &[],
+};
+
+pub const SG_ALL_TRAFFIC_INGRESS: RuleInfo = RuleInfo {
+    id: "TG-SG-010",
+    title: "Security group allows inbound traffic on all protocols and ports",
+    remediation: "Replace protocol = \"-1\" with rules for the specific protocols and ports the service needs",
+    doc_url: SG_DOCS,
+    compliance: &["AWS FSBP EC2.2"],
+};
+
+pub const PLAN_UNKNOWN_VALUE: RuleInfo = RuleInfo {
+    id: "TG-PLAN-001",
+    title: "Security-relevant value cannot be determined",
+    remediation: "Review the value once it is known, or set it from a literal or a value known at plan time so it can be checked",
+    doc_url: Some("https://developer.hashicorp.com/terraform/internals/json-format#change-representation"),
+    compliance: &[],
+};
+
+pub const UNUSED_SUPPRESSION: RuleInfo = RuleInfo {
+    id: "TG-IGNORE-001",
+    title: "Inline suppression comment does not match any finding",
+    remediation: "Remove the terraguard:ignore comment, or correct its rule id if it was mistyped",
+    doc_url: None,
+    compliance: &[],
+};
+
+pub const EC2_PUBLIC_IP: RuleInfo = RuleInfo {
+    id: "TG-EC2-001",
+    title: "EC2 instance has a public IP address",
+    remediation: "Set associate_public_ip_address = false and expose the instance through a load balancer or NAT",
+    doc_url: Some("https://docs.aws.amazon.com/AWSEC2/latest/UserGuide/using-instance-addressing.html"),
+    compliance: &["AWS FSBP EC2.9"],
+};
+
+pub const EC2_IMDSV2: RuleInfo = RuleInfo {
+    id: "TG-EC2-002",
+    title: "EC2 instance does not enforce IMDSv2",
+    remediation: "Set metadata_options { http_tokens = \"required\" }",
+    doc_url: Some("https://docs.aws.amazon.com/AWSEC2/latest/UserGuide/configuring-instance-metadata-service.html"),
+    compliance: &["CIS AWS 5.6", "AWS FSBP EC2.8"],
+};
+
+pub const S3_PUBLIC_ACL: RuleInfo = RuleInfo {
+    id: "TG-S3-001",
+    title: "S3 bucket is publicly accessible through its ACL",
+    remediation: "Use a private ACL and enable S3 Block Public Access",
+    doc_url: Some("https://docs.aws.amazon.com/AmazonS3/latest/userguide/access-control-block-public-access.html"),
+    compliance: &["AWS FSBP S3.2"],
+};
+
+pub const S3_NO_ENCRYPTION: RuleInfo = RuleInfo {
+    id: "TG-S3-002",
+    title: "S3 bucket is missing server-side encryption",
+    remediation: "Configure server_side_encryption_configuration with SSE-S3 or SSE-KMS",
+    doc_url: Some("https://docs.aws.amazon.com/AmazonS3/latest/userguide/serv-side-encryption.html"),
+    compliance: &["CIS AWS 2.1.1"],
+};
+
+pub const S3_NO_VERSIONING: RuleInfo = RuleInfo {
+    id: "TG-S3-003",
+    title: "S3 bucket versioning is not enabled",
+    remediation: "Enable versioning so objects can be recovered after accidental deletion or overwrite",
+    doc_url: Some("https://docs.aws.amazon.com/AmazonS3/latest/userguide/Versioning.html"),
+    compliance: &[],
+};
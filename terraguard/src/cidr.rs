@@ -0,0 +1,202 @@
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+// Public ranges at least this broad are treated as open to the internet
+const BROAD_V4_PREFIX: u8 = 16;
+const BROAD_V6_PREFIX: u8 = 32;
+
+/// What kind of addresses a CIDR range covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeClass {
+    // Contains internet-routable addresses
+    Public,
+    // RFC1918, carrier-grade NAT or IPv6 unique local addresses
+    Private,
+    Loopback,
+    LinkLocal,
+    // Multicast, "this network" and other non-unicast space
+    Reserved,
+}
+
+impl fmt::Display for RangeClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RangeClass::Public => "public",
+            RangeClass::Private => "private",
+            RangeClass::Loopback => "loopback",
+            RangeClass::LinkLocal => "link-local",
+            RangeClass::Reserved => "reserved",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// An IPv4 or IPv6 network in CIDR notation, normalized to its network address
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cidr {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+// Well-known non-public IPv4 blocks
+const V4_BLOCKS: &[(Ipv4Addr, u8, RangeClass)] = &[
+    (Ipv4Addr::new(10, 0, 0, 0), 8, RangeClass::Private),
+    (Ipv4Addr::new(172, 16, 0, 0), 12, RangeClass::Private),
+    (Ipv4Addr::new(192, 168, 0, 0), 16, RangeClass::Private),
+    (Ipv4Addr::new(100, 64, 0, 0), 10, RangeClass::Private),
+    (Ipv4Addr::new(127, 0, 0, 0), 8, RangeClass::Loopback),
+    (Ipv4Addr::new(169, 254, 0, 0), 16, RangeClass::LinkLocal),
+    (Ipv4Addr::new(0, 0, 0, 0), 8, RangeClass::Reserved),
+    (Ipv4Addr::new(224, 0, 0, 0), 4, RangeClass::Reserved),
+    (Ipv4Addr::new(240, 0, 0, 0), 4, RangeClass::Reserved),
+];
+
+// Well-known non-public IPv6 blocks
+const V6_BLOCKS: &[(Ipv6Addr, u8, RangeClass)] = &[
+    (Ipv6Addr::new(0xfc00, 0, 0, 0, 0, 0, 0, 0), 7, RangeClass::Private),
+    (Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1), 128, RangeClass::Loopback),
+    (Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 0), 10, RangeClass::LinkLocal),
+    (Ipv6Addr::new(0xff00, 0, 0, 0, 0, 0, 0, 0), 8, RangeClass::Reserved),
+    (Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0), 128, RangeClass::Reserved),
+];
+
+impl Cidr {
+    /// Parse "a.b.c.d/n" or "x::y/n", ignoring surrounding whitespace. A bare
+    /// address is treated as a single host.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let (addr, prefix) = match text.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (text, None),
+        };
+
+        let addr: IpAddr = addr.parse()
+            .map_err(|_| format!("'{}' is not a valid IP address", addr))?;
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(p) => p.parse::<u8>().ok().filter(|p| *p <= max)
+                .ok_or_else(|| format!("'{}' is not a valid prefix length for {}", p, addr))?,
+            None => max,
+        };
+
+        Ok(Self::new(addr, prefix))
+    }
+
+    fn new(addr: IpAddr, prefix: u8) -> Self {
+        // Clear host bits so 10.1.2.3/8 and 10.0.0.0/8 compare equal
+        let addr = match addr {
+            IpAddr::V4(v4) => IpAddr::V4(Ipv4Addr::from(u32::from(v4) & v4_mask(prefix))),
+            IpAddr::V6(v6) => IpAddr::V6(Ipv6Addr::from(u128::from(v6) & v6_mask(prefix))),
+        };
+        Self { addr, prefix }
+    }
+
+    pub fn is_ipv6(&self) -> bool {
+        self.addr.is_ipv6()
+    }
+
+    /// Number of addresses in the range, saturating at `u128::MAX` for `::/0`
+    pub fn host_count(&self) -> u128 {
+        let bits = if self.addr.is_ipv4() { 32 } else { 128 } - self.prefix as u32;
+        1u128.checked_shl(bits).unwrap_or(u128::MAX)
+    }
+
+    /// Whether `other` lies entirely within this range
+    pub fn contains(&self, other: &Cidr) -> bool {
+        if self.prefix > other.prefix {
+            return false;
+        }
+        match (self.addr, other.addr) {
+            (IpAddr::V4(a), IpAddr::V4(b)) => u32::from(b) & v4_mask(self.prefix) == u32::from(a),
+            (IpAddr::V6(a), IpAddr::V6(b)) => u128::from(b) & v6_mask(self.prefix) == u128::from(a),
+            _ => false,
+        }
+    }
+
+    /// Classify the range. A range only counts as private, loopback, ... if it
+    /// lies entirely inside such a block; anything else contains public addresses.
+    pub fn classify(&self) -> RangeClass {
+        let known = match self.addr {
+            IpAddr::V4(_) => V4_BLOCKS.iter()
+                .map(|(addr, prefix, class)| (Cidr::new(IpAddr::V4(*addr), *prefix), *class))
+                .find(|(block, _)| block.contains(self)),
+            IpAddr::V6(_) => V6_BLOCKS.iter()
+                .map(|(addr, prefix, class)| (Cidr::new(IpAddr::V6(*addr), *prefix), *class))
+                .find(|(block, _)| block.contains(self)),
+        };
+        known.map(|(_, class)| class).unwrap_or(RangeClass::Public)
+    }
+
+    /// True for the whole address space (0.0.0.0/0, ::/0)
+    pub fn is_any(&self) -> bool {
+        self.prefix == 0
+    }
+
+    /// Whether the range opens access to a broad slice of the internet
+    pub fn is_internet_exposed(&self) -> bool {
+        let broad = if self.is_ipv6() { BROAD_V6_PREFIX } else { BROAD_V4_PREFIX };
+        self.classify() == RangeClass::Public && self.prefix <= broad
+    }
+
+    /// Human-readable description used in finding messages
+    pub fn describe(&self) -> String {
+        if self.is_any() {
+            let family = if self.is_ipv6() { "IPv6" } else { "IPv4" };
+            return format!("all {} addresses", family);
+        }
+        format!("{}, {} addresses", self.classify(), self.host_count())
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+fn v4_mask(prefix: u8) -> u32 {
+    u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0)
+}
+
+fn v6_mask(prefix: u8) -> u128 {
+    u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_normalize() {
+        assert_eq!(Cidr::parse(" 0.0.0.0/0 ").unwrap().to_string(), "0.0.0.0/0");
+        assert_eq!(Cidr::parse("10.1.2.3/8").unwrap().to_string(), "10.0.0.0/8");
+        assert_eq!(Cidr::parse("2001:db8::1").unwrap().to_string(), "2001:db8::1/128");
+        assert!(Cidr::parse("10.0.0.0/33").is_err());
+        assert!(Cidr::parse("10.0.0/8").is_err());
+        assert!(Cidr::parse("any").is_err());
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(Cidr::parse("10.0.0.0/8").unwrap().classify(), RangeClass::Private);
+        assert_eq!(Cidr::parse("172.20.0.0/16").unwrap().classify(), RangeClass::Private);
+        assert_eq!(Cidr::parse("127.0.0.1/32").unwrap().classify(), RangeClass::Loopback);
+        assert_eq!(Cidr::parse("169.254.169.254/32").unwrap().classify(), RangeClass::LinkLocal);
+        assert_eq!(Cidr::parse("fd00::/8").unwrap().classify(), RangeClass::Private);
+        assert_eq!(Cidr::parse("fe80::/64").unwrap().classify(), RangeClass::LinkLocal);
+        // Wider than the private block, so it also covers public space
+        assert_eq!(Cidr::parse("10.0.0.0/7").unwrap().classify(), RangeClass::Public);
+    }
+
+    #[test]
+    fn test_internet_exposure() {
+        assert!(Cidr::parse("0.0.0.0/1").unwrap().is_internet_exposed());
+        assert!(Cidr::parse("128.0.0.0/1").unwrap().is_internet_exposed());
+        assert!(Cidr::parse("1.0.0.0/8").unwrap().is_internet_exposed());
+        assert!(Cidr::parse("::/0").unwrap().is_internet_exposed());
+        assert!(!Cidr::parse("203.0.113.10/32").unwrap().is_internet_exposed());
+        assert!(!Cidr::parse("10.0.0.0/8").unwrap().is_internet_exposed());
+        assert_eq!(Cidr::parse("1.0.0.0/8").unwrap().host_count(), 16_777_216);
+        assert_eq!(Cidr::parse("::/0").unwrap().host_count(), u128::MAX);
+    }
+}
@@ -0,0 +1,349 @@
+use serde_json::{json, Map, Value};
+
+/// A template resource with its `Properties` mapped onto the attribute names
+/// the built-in checks use, e.g. `SecurityGroupIngress[].CidrIp` becomes
+/// `ingress[].cidr_blocks`
+#[derive(Debug, Clone)]
+pub struct TemplateResource {
+    pub logical_id: String,
+    // CloudFormation type such as `AWS::EC2::SecurityGroup`
+    pub resource_type: String,
+    pub values: Value,
+    // Properties set through intrinsic functions, marked `true` like a plan's `after_unknown`
+    pub unknown: Value,
+}
+
+/// Whether a document looks like a CloudFormation template
+pub fn is_template(document: &Value) -> bool {
+    ["Resources", "resources"].iter().any(|key| document.get(key).is_some_and(Value::is_object))
+}
+
+/// Parse a JSON or YAML template. Short-form intrinsics such as `!Ref Name` or
+/// `!GetAtt Group.GroupId` are expanded to their JSON form.
+pub fn parse_template(text: &str) -> Result<Value, String> {
+    if let Ok(template) = serde_json::from_str(text) {
+        return Ok(template);
+    }
+    let yaml: serde_yaml::Value = serde_yaml::from_str(text)
+        .map_err(|e| format!("invalid YAML template: {}", e))?;
+    Ok(yaml_to_json(yaml))
+}
+
+fn yaml_to_json(value: serde_yaml::Value) -> Value {
+    use serde_yaml::Value as Yaml;
+
+    match value {
+        Yaml::Null => Value::Null,
+        Yaml::Bool(b) => Value::Bool(b),
+        Yaml::Number(n) => n.as_i64().map(Value::from)
+            .or_else(|| n.as_u64().map(Value::from))
+            .or_else(|| n.as_f64().map(Value::from))
+            .unwrap_or(Value::Null),
+        Yaml::String(s) => Value::String(s),
+        Yaml::Sequence(items) => Value::Array(items.into_iter().map(yaml_to_json).collect()),
+        Yaml::Mapping(mapping) => Value::Object(mapping.into_iter().map(|(key, value)| {
+            let key = match key {
+                Yaml::String(s) => s,
+                other => serde_yaml::to_string(&other).unwrap_or_default().trim().to_string(),
+            };
+            (key, yaml_to_json(value))
+        }).collect()),
+        Yaml::Tagged(tagged) => {
+            let tag = tagged.tag.to_string();
+            let name = tag.trim_start_matches('!');
+            let inner = yaml_to_json(tagged.value);
+            match (name, inner) {
+                ("Ref" | "Condition", inner) => json!({ name: inner }),
+                // `!GetAtt Resource.Attribute` is the short form of ["Resource", "Attribute"]
+                ("GetAtt", Value::String(path)) => match path.split_once('.') {
+                    Some((resource, attribute)) => json!({ "Fn::GetAtt": [resource, attribute] }),
+                    None => json!({ "Fn::GetAtt": path }),
+                },
+                (name, inner) => json!({ format!("Fn::{}", name): inner }),
+            }
+        }
+    }
+}
+
+/// The resources of a template, with properties mapped for the built-in checks.
+/// Types without a mapping keep their properties as they are.
+pub fn template_resources(template: &Value) -> Vec<TemplateResource> {
+    let parameters = template.get("Parameters").unwrap_or(&Value::Null);
+    let resources = template.get("Resources").or_else(|| template.get("resources")).and_then(|r| r.as_object());
+
+    resources.into_iter().flatten().map(|(logical_id, resource)| {
+        let resource_type = resource.get("Type").and_then(|t| t.as_str()).unwrap_or("(untyped)");
+        let properties = resource.get("Properties").unwrap_or(&Value::Null);
+        let mut mapper = Mapper { parameters, values: Map::new(), unknown: Map::new() };
+
+        match resource_type {
+            "AWS::EC2::SecurityGroup" => mapper.security_group(properties),
+            "AWS::EC2::SecurityGroupIngress" => mapper.standalone_rule(properties, "ingress"),
+            "AWS::EC2::SecurityGroupEgress" => mapper.standalone_rule(properties, "egress"),
+            "AWS::EC2::Instance" => mapper.instance(properties),
+            "AWS::S3::Bucket" => mapper.bucket(properties),
+            _ => {
+                if let Some(properties) = properties.as_object() {
+                    mapper.values = properties.clone();
+                }
+            }
+        }
+
+        TemplateResource {
+            logical_id: logical_id.clone(),
+            resource_type: resource_type.to_string(),
+            values: Value::Object(mapper.values),
+            unknown: Value::Object(mapper.unknown),
+        }
+    }).collect()
+}
+
+// Properties of a security group rule that the checks read
+const RULE_PROPERTIES: [&str; 5] = ["IpProtocol", "FromPort", "ToPort", "CidrIp", "CidrIpv6"];
+
+/// Builds the mapped values of one resource, and the tree of values that
+/// are only known once the stack is deployed
+struct Mapper<'a> {
+    parameters: &'a Value,
+    values: Map<String, Value>,
+    unknown: Map<String, Value>,
+}
+
+impl Mapper<'_> {
+    /// Resolve a property: literals as they are, `Ref`s to parameters with a
+    /// default to that default. Any other intrinsic is unknown (`None`).
+    fn resolve(&self, property: &Value) -> Option<Value> {
+        let Some((function, argument)) = property.as_object().filter(|o| o.len() == 1).and_then(|o| o.iter().next()) else {
+            return Some(property.clone());
+        };
+        match function.as_str() {
+            "Ref" => argument.as_str()
+                .and_then(|name| self.parameters.get(name))
+                .and_then(|parameter| parameter.get("Default"))
+                .cloned(),
+            "Condition" => None,
+            name if name.starts_with("Fn::") => None,
+            _ => Some(property.clone()),
+        }
+    }
+
+    /// Set `key` from `property`, or mark it unknown when it cannot be resolved
+    fn set(&mut self, key: &str, property: Option<&Value>, convert: fn(Value) -> Value) {
+        let Some(property) = property else {
+            return;
+        };
+        match self.resolve(property) {
+            Some(value) => {
+                self.values.insert(key.to_string(), convert(value));
+            }
+            None => {
+                self.unknown.insert(key.to_string(), Value::Bool(true));
+            }
+        }
+    }
+
+    /// A rule in the inline `ingress`/`egress` shape, with its unknown fields
+    fn rule(&self, properties: &Value) -> (Value, Value) {
+        let mut rule = Mapper { parameters: self.parameters, values: Map::new(), unknown: Map::new() };
+        rule.set("protocol", properties.get("IpProtocol"), protocol);
+        rule.set("from_port", properties.get("FromPort"), number);
+        rule.set("to_port", properties.get("ToPort"), number);
+        rule.set("cidr_blocks", properties.get("CidrIp"), list);
+        rule.set("ipv6_cidr_blocks", properties.get("CidrIpv6"), list);
+        (Value::Object(rule.values), Value::Object(rule.unknown))
+    }
+
+    fn security_group(&mut self, properties: &Value) {
+        for (property, key) in [("SecurityGroupIngress", "ingress"), ("SecurityGroupEgress", "egress")] {
+            let Some(rules) = properties.get(property) else {
+                continue;
+            };
+            match self.resolve(rules) {
+                Some(Value::Array(rules)) => {
+                    let (values, unknown): (Vec<Value>, Vec<Value>) = rules.iter().map(|rule| match self.resolve(rule) {
+                        Some(rule) if RULE_PROPERTIES.iter().any(|property| rule.get(property).is_some()) => self.rule(&rule),
+                        // Entries such as `!If [Cond, {...}, !Ref AWS::NoValue]` are only known once deployed
+                        _ => (Value::Null, Value::Bool(true)),
+                    }).unzip();
+                    self.values.insert(key.to_string(), Value::Array(values));
+                    self.unknown.insert(key.to_string(), Value::Array(unknown));
+                }
+                Some(_) => {}
+                None => {
+                    self.unknown.insert(key.to_string(), Value::Bool(true));
+                }
+            }
+        }
+        self.set("tags", properties.get("Tags"), tags);
+    }
+
+    fn standalone_rule(&mut self, properties: &Value, direction: &str) {
+        let (values, unknown) = self.rule(properties);
+        self.values = values.as_object().cloned().unwrap_or_default();
+        self.unknown = unknown.as_object().cloned().unwrap_or_default();
+        self.values.insert("type".to_string(), json!(direction));
+
+        // `!Ref Group` and `!GetAtt Group.GroupId` both name the parent by logical id
+        let group = properties.get("GroupId").and_then(|group| {
+            group.get("Ref").and_then(|r| r.as_str())
+                .or_else(|| group.get("Fn::GetAtt").and_then(|a| a.get(0)).and_then(|r| r.as_str()))
+                .or_else(|| group.as_str())
+        });
+        if let Some(group) = group {
+            self.values.insert("security_group_id".to_string(), json!(group));
+        }
+    }
+
+    fn instance(&mut self, properties: &Value) {
+        let interfaces = properties.get("NetworkInterfaces").and_then(|n| n.as_array());
+        for interface in interfaces.into_iter().flatten() {
+            self.set("associate_public_ip_address", interface.get("AssociatePublicIpAddress"), boolean);
+            if self.values.get("associate_public_ip_address") == Some(&Value::Bool(true)) {
+                break;
+            }
+        }
+
+        if let Some(options) = properties.get("MetadataOptions") {
+            let mut metadata = Mapper { parameters: self.parameters, values: Map::new(), unknown: Map::new() };
+            metadata.set("http_tokens", options.get("HttpTokens"), |v| v);
+            self.values.insert("metadata_options".to_string(), Value::Object(metadata.values));
+            self.unknown.insert("metadata_options".to_string(), Value::Object(metadata.unknown));
+        }
+    }
+
+    fn bucket(&mut self, properties: &Value) {
+        self.set("acl", properties.get("AccessControl"), canned_acl);
+        self.set("server_side_encryption_configuration", properties.get("BucketEncryption"), |v| v);
+
+        if let Some(versioning) = properties.get("VersioningConfiguration") {
+            let mut status = Mapper { parameters: self.parameters, values: Map::new(), unknown: Map::new() };
+            status.set("enabled", versioning.get("Status"), |s| json!(s == "Enabled"));
+            self.values.insert("versioning".to_string(), Value::Object(status.values));
+            self.unknown.insert("versioning".to_string(), Value::Object(status.unknown));
+        }
+    }
+}
+
+/// Ports may be written as strings in templates
+fn number(value: Value) -> Value {
+    match value.as_str().and_then(|s| s.trim().parse::<i64>().ok()) {
+        Some(port) => json!(port),
+        None => value,
+    }
+}
+
+fn protocol(value: Value) -> Value {
+    match value {
+        Value::Number(n) => Value::String(n.to_string()),
+        other => other,
+    }
+}
+
+fn list(value: Value) -> Value {
+    json!([value])
+}
+
+fn boolean(value: Value) -> Value {
+    match value.as_str() {
+        Some(text) => json!(text.eq_ignore_ascii_case("true")),
+        None => value,
+    }
+}
+
+/// `Tags: [{Key, Value}]` as a map
+fn tags(value: Value) -> Value {
+    let tags: Map<String, Value> = value.as_array().into_iter().flatten()
+        .filter_map(|tag| Some((tag.get("Key")?.as_str()?.to_string(), tag.get("Value")?.clone())))
+        .collect();
+    Value::Object(tags)
+}
+
+/// `PublicReadWrite` as the canned ACL `public-read-write`
+fn canned_acl(value: Value) -> Value {
+    let Some(name) = value.as_str() else {
+        return value;
+    };
+    let mut acl = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            acl.push('-');
+        }
+        acl.push(c.to_ascii_lowercase());
+    }
+    Value::String(acl)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_yaml_short_form_intrinsics() {
+        let template = parse_template(r#"
+Parameters:
+  AdminCidr:
+    Type: String
+    Default: 0.0.0.0/0
+Resources:
+  WebSG:
+    Type: AWS::EC2::SecurityGroup
+    Properties:
+      GroupDescription: web
+      SecurityGroupIngress:
+        - IpProtocol: tcp
+          FromPort: "22"
+          ToPort: 22
+          CidrIp: !Ref AdminCidr
+        - IpProtocol: tcp
+          FromPort: 443
+          ToPort: 443
+          CidrIp: !ImportValue OfficeCidr
+  Bucket:
+    Type: AWS::S3::Bucket
+    Properties:
+      AccessControl: PublicRead
+      BucketEncryption: !If [Encrypt, { ServerSideEncryptionConfiguration: [] }, !Ref AWS::NoValue]
+"#).unwrap();
+
+        let resources = template_resources(&template);
+        let group = resources.iter().find(|r| r.logical_id == "WebSG").unwrap();
+        assert_eq!(group.values["ingress"][0], json!({ "protocol": "tcp", "from_port": 22, "to_port": 22, "cidr_blocks": ["0.0.0.0/0"] }));
+        assert_eq!(group.unknown["ingress"][1], json!({ "cidr_blocks": true }));
+
+        let bucket = resources.iter().find(|r| r.logical_id == "Bucket").unwrap();
+        assert_eq!(bucket.values["acl"], "public-read");
+        assert_eq!(bucket.unknown["server_side_encryption_configuration"], true);
+
+        let issues = crate::rules::FastValidator::new().validate_template(&template);
+        let unknown = issues.iter().find(|issue| issue.rule_id == "TG-PLAN-001").unwrap();
+        assert!(unknown.message.contains("from the template; it depends on a parameter"));
+    }
+
+    #[test]
+    fn test_conditional_rule_entries_are_unknown() {
+        let template = json!({
+            "Resources": {
+                "WebSG": {
+                    "Type": "AWS::EC2::SecurityGroup",
+                    "Properties": {
+                        "Tags": [{ "Key": "Name", "Value": "web" }],
+                        "SecurityGroupIngress": [
+                            { "IpProtocol": "tcp", "FromPort": 443, "ToPort": 443, "CidrIp": "10.0.0.0/8" },
+                            { "Fn::If": ["Public", { "IpProtocol": "tcp", "FromPort": 22, "ToPort": 22, "CidrIp": "0.0.0.0/0" }, { "Ref": "AWS::NoValue" }] }
+                        ]
+                    }
+                }
+            }
+        });
+
+        let group = &template_resources(&template)[0];
+        assert_eq!(group.values["ingress"][1], Value::Null);
+        assert_eq!(group.unknown["ingress"], json!([{}, true]));
+
+        // Not read as an all-traffic rule, but reported as undeterminable
+        let issues = crate::rules::FastValidator::new().validate_template(&template);
+        let rules: Vec<&str> = issues.iter().map(|issue| issue.rule_id.as_str()).collect();
+        assert_eq!(rules, ["TG-PLAN-001"]);
+        assert_eq!(issues[0].attributes["attribute"], "ingress.1");
+    }
+}
@@ -0,0 +1,216 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::ports::{PortCatalog, PortCatalogFile};
+use crate::report::OutputFormat;
+use crate::suppression::glob_match;
+use crate::types::{Issue, Severity};
+
+/// Project configuration file discovered by walking up from the working directory
+pub const CONFIG_FILE: &str = ".terraguard.toml";
+
+/// Contents of a `.terraguard.toml`. Relative paths are resolved against the
+/// directory containing the file.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub policies: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ignore_file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<OutputFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fail_on: Option<Severity>,
+    // Report identical findings on count/for_each instances once, as `address[*]`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collapse_instances: Option<bool>,
+    // Environment and application whose port catalog overrides apply
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub application: Option<String>,
+    #[serde(default)]
+    pub rules: RuleSettings,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ports: Option<PortCatalogFile>,
+}
+
+/// Which rules are reported and at what severity. Rule ids may be globs such as `TG-S3-*`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleSettings {
+    // When non-empty, only matching rules are reported
+    #[serde(default)]
+    pub enabled: Vec<String>,
+    #[serde(default)]
+    pub disabled: Vec<String>,
+    // Severity reported instead of the rule's own
+    #[serde(default)]
+    pub severity: BTreeMap<String, Severity>,
+}
+
+impl RuleSettings {
+    /// Whether findings of a rule are reported
+    pub fn is_enabled(&self, rule_id: &str) -> bool {
+        let listed = |patterns: &[String]| patterns.iter().any(|p| glob_match(p, rule_id));
+        (self.enabled.is_empty() || listed(&self.enabled)) && !listed(&self.disabled)
+    }
+
+    /// Severity override for a rule; an exact id wins over a glob
+    pub fn severity_for(&self, rule_id: &str) -> Option<Severity> {
+        self.severity.get(rule_id)
+            .or_else(|| self.severity.iter().find(|(p, _)| glob_match(p, rule_id)).map(|(_, s)| s))
+            .cloned()
+    }
+
+    /// Drop findings of disabled rules and apply severity overrides
+    pub fn apply(&self, issues: Vec<Issue>) -> Vec<Issue> {
+        issues.into_iter()
+            .filter(|issue| self.is_enabled(&issue.rule_id))
+            .map(|mut issue| {
+                if let Some(severity) = self.severity_for(&issue.rule_id) {
+                    issue.severity = severity;
+                }
+                issue
+            })
+            .collect()
+    }
+}
+
+impl ConfigFile {
+    /// Load a config file, resolving relative paths against its directory
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("could not read config file {}: {}", path.display(), e))?;
+        let mut config: ConfigFile = toml::from_str(&content)
+            .map_err(|e| format!("invalid config file {}: {}", path.display(), e))?;
+
+        let base = path.parent().unwrap_or(Path::new("."));
+        let resolve = |p: &String| base.join(p).to_string_lossy().into_owned();
+        config.policies = config.policies.iter().map(resolve).collect();
+        config.ignore_file = config.ignore_file.as_ref().map(resolve);
+
+        // Surface malformed ports now rather than on first use
+        if let Some(ports) = &config.ports {
+            ports.resolve(config.environment.as_deref(), config.application.as_deref())
+                .map_err(|e| format!("{} in [ports] of {}", e, path.display()))?;
+        }
+
+        Ok(config)
+    }
+
+    /// Find the nearest `.terraguard.toml` in `start` or one of its parents
+    pub fn discover(start: &Path) -> Result<Option<(PathBuf, Self)>, String> {
+        for dir in start.ancestors() {
+            let candidate = dir.join(CONFIG_FILE);
+            if candidate.is_file() {
+                return Self::load(&candidate).map(|config| Some((candidate, config)));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// The configuration a command runs with after merging CLI flags, the config
+/// file and defaults, in that order of precedence
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub config_file: Option<PathBuf>,
+    pub policies: Vec<String>,
+    pub ignore_file: Option<String>,
+    pub format: OutputFormat,
+    pub fail_on: Option<Severity>,
+    pub collapse_instances: bool,
+    pub environment: Option<String>,
+    pub application: Option<String>,
+    pub rules: RuleSettings,
+    pub ports: PortCatalog,
+}
+
+/// Serialized form of `Settings` printed by `tg config show`
+#[derive(Serialize)]
+struct SettingsView<'a> {
+    policies: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ignore_file: Option<&'a str>,
+    format: OutputFormat,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fail_on: Option<&'a Severity>,
+    collapse_instances: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    environment: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    application: Option<&'a str>,
+    rules: &'a RuleSettings,
+    ports: PortsView,
+}
+
+#[derive(Serialize)]
+struct PortsView {
+    allowed: Vec<String>,
+    disallowed: Vec<String>,
+    services: BTreeMap<String, String>,
+}
+
+impl Settings {
+    /// Render the effective configuration as TOML, with the resolved port catalog
+    pub fn to_toml(&self) -> Result<String, String> {
+        let view = SettingsView {
+            policies: &self.policies,
+            ignore_file: self.ignore_file.as_deref(),
+            format: self.format,
+            fail_on: self.fail_on.as_ref(),
+            collapse_instances: self.collapse_instances,
+            environment: self.environment.as_deref(),
+            application: self.application.as_deref(),
+            rules: &self.rules,
+            ports: PortsView {
+                allowed: self.ports.allowed.iter().map(|p| p.to_string()).collect(),
+                disallowed: self.ports.disallowed.iter().map(|p| p.to_string()).collect(),
+                services: self.ports.services.iter().map(|(p, name)| (p.to_string(), name.clone())).collect(),
+            },
+        };
+
+        let source = match &self.config_file {
+            Some(path) => format!("# Effective configuration, using {}\n", path.display()),
+            None => format!("# Effective configuration, no {} found\n", CONFIG_FILE),
+        };
+        toml::to_string(&view)
+            .map(|body| source + &body)
+            .map_err(|e| format!("could not render configuration: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::{SG_MISSING_TAGS, SG_PORT_NOT_ALLOWED, S3_NO_VERSIONING};
+
+    #[test]
+    fn test_rule_settings() {
+        let config: ConfigFile = toml::from_str(r#"
+format = "sarif"
+fail_on = "high"
+
+[rules]
+disabled = ["TG-S3-*"]
+
+[rules.severity]
+"TG-SG-004" = "high"
+
+[ports]
+allowed = ["tcp/9443"]
+"#).unwrap();
+        assert_eq!(config.format, Some(OutputFormat::Sarif));
+
+        let issue = |rule| Issue::new(rule, "aws_security_group.sg".to_string(), "message".to_string(), Severity::Medium);
+        let issues = config.rules.apply(vec![issue(&SG_PORT_NOT_ALLOWED), issue(&SG_MISSING_TAGS), issue(&S3_NO_VERSIONING)]);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].severity, Severity::High);
+        assert_eq!(issues[1].severity, Severity::Medium);
+
+        assert!(toml::from_str::<ConfigFile>("polices = []").is_err());
+    }
+}
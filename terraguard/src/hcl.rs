@@ -0,0 +1,368 @@
+use crate::suppression::InlineSuppression;
+use crate::types::SourceLocation;
+use hcl_edit::expr::{Expression, ObjectKey, UnaryOperator};
+use hcl_edit::structure::Body;
+use hcl_edit::{Number, Span};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+// Arguments Terraform interprets itself rather than passing to the provider
+const META_ARGUMENTS: [&str; 4] = ["count", "for_each", "provider", "depends_on"];
+const META_BLOCKS: [&str; 3] = ["lifecycle", "provisioner", "connection"];
+
+// Start of an inline suppression comment: `# terraguard:ignore=TG-SG-001,TG-SG-003 reason="..."`
+const IGNORE_MARKER: &str = "terraguard:ignore=";
+
+/// A `resource` block of a `.tf` file with its literal attributes in the shape
+/// of a plan's `values`: nested blocks such as `ingress { }` become lists
+#[derive(Debug, Clone)]
+pub struct SourceResource {
+    // Configuration address such as `aws_security_group.web`
+    pub address: String,
+    pub resource_type: String,
+    pub values: Value,
+    // Attributes that are not literals (variables, references, function
+    // calls, dynamic blocks), marked `true` like a plan's `after_unknown`
+    pub unknown: Value,
+    // Attributes that reference another object, e.g. `security_group_id` → `aws_security_group.web.id`
+    pub references: BTreeMap<String, String>,
+    pub location: SourceLocation,
+    // `terraguard:ignore` comments above or inside the block
+    pub suppressions: Vec<InlineSuppression>,
+}
+
+/// Every `.tf` file under `path`, in a stable order. Hidden directories such
+/// as `.terraform` (downloaded modules and providers) are skipped.
+pub fn source_files(path: &Path) -> Result<Vec<PathBuf>, String> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut entries: Vec<PathBuf> = fs::read_dir(path)
+        .map_err(|e| format!("could not read directory {}: {}", path.display(), e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    entries.sort();
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let hidden = entry.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if entry.is_dir() && !hidden {
+            files.extend(source_files(&entry)?);
+        } else if entry.extension().is_some_and(|ext| ext == "tf") {
+            files.push(entry);
+        }
+    }
+    Ok(files)
+}
+
+/// Parse the `resource` blocks of every `.tf` file under `path`
+pub fn load_sources(path: &Path) -> Result<Vec<SourceResource>, String> {
+    let mut resources = Vec::new();
+    for file in source_files(path)? {
+        let text = fs::read_to_string(&file)
+            .map_err(|e| format!("could not read {}: {}", file.display(), e))?;
+        resources.extend(parse_source(&text, &file.to_string_lossy())?);
+    }
+    Ok(resources)
+}
+
+/// Parse the `resource` blocks of one `.tf` file. `file` is recorded in each
+/// block's location.
+pub fn parse_source(text: &str, file: &str) -> Result<Vec<SourceResource>, String> {
+    let body = hcl_edit::parser::parse_body(text)
+        .map_err(|e| format!("invalid HCL in {}: {}", file, e))?;
+
+    let mut resources = Vec::new();
+    for block in body.get_blocks("resource") {
+        let [resource_type, name] = block.labels.as_slice() else {
+            continue;
+        };
+        let address = format!("{}.{}", resource_type.as_str(), name.as_str());
+        let span = block.span().unwrap_or(0..0);
+        let (values, unknown) = body_values(&block.body);
+        let references = block.body.attributes()
+            .filter(|attribute| matches!(attribute.value, Expression::Traversal(_)))
+            .map(|attribute| (attribute.key.to_string(), attribute.value.to_string().trim().to_string()))
+            .collect();
+
+        resources.push(SourceResource {
+            suppressions: inline_suppressions(text, file, &address, &span)?,
+            address,
+            resource_type: resource_type.as_str().to_string(),
+            values: Value::Object(values),
+            unknown: Value::Object(unknown),
+            references,
+            location: location(text, file, span.start),
+        });
+    }
+    Ok(resources)
+}
+
+/// Line and column of a byte offset
+fn location(text: &str, file: &str, offset: usize) -> SourceLocation {
+    let before = &text[..offset];
+    SourceLocation {
+        file: file.to_string(),
+        line: before.matches('\n').count() + 1,
+        column: before.rsplit('\n').next().unwrap_or_default().chars().count() + 1,
+    }
+}
+
+/// The `terraguard:ignore` comments on the lines directly above a block and
+/// inside it. A comment without a `reason="..."` is an error.
+fn inline_suppressions(text: &str, file: &str, address: &str, span: &Range<usize>) -> Result<Vec<InlineSuppression>, String> {
+    let lines: Vec<&str> = text.split('\n').collect();
+    let last = text[..span.end].matches('\n').count();
+    let mut first = text[..span.start].matches('\n').count();
+    while first > 0 && is_comment(lines[first - 1]) {
+        first -= 1;
+    }
+
+    let mut suppressions = Vec::new();
+    for (index, line) in lines.iter().enumerate().take(last + 1).skip(first) {
+        let Some(marker) = line.find(IGNORE_MARKER) else {
+            continue;
+        };
+        let Some(comment) = line[..marker].rfind('#').or_else(|| line[..marker].rfind("//")) else {
+            continue;
+        };
+
+        let directive = &line[marker + IGNORE_MARKER.len()..];
+        let rules = directive.split_whitespace().next().unwrap_or_default();
+        let reason = directive.split_once("reason=\"")
+            .and_then(|(_, rest)| rest.split_once('"'))
+            .map(|(reason, _)| reason.trim())
+            .unwrap_or_default();
+        let location = SourceLocation {
+            file: file.to_string(),
+            line: index + 1,
+            column: line[..comment].chars().count() + 1,
+        };
+        if reason.is_empty() {
+            return Err(format!("suppression of {} on {} at {} has no reason", rules, address, location));
+        }
+
+        for rule in rules.split(',').filter(|rule| !rule.is_empty()) {
+            suppressions.push(InlineSuppression {
+                rule: rule.to_string(),
+                resource: address.to_string(),
+                reason: reason.to_string(),
+                location: location.clone(),
+                suppressed: Vec::new(),
+            });
+        }
+    }
+    Ok(suppressions)
+}
+
+fn is_comment(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with('#') || line.starts_with("//")
+}
+
+/// The literal values of a block body and the tree of its unknown values
+fn body_values(body: &Body) -> (Map<String, Value>, Map<String, Value>) {
+    let mut values = Map::new();
+    let mut unknown = Map::new();
+
+    for attribute in body.attributes() {
+        let key = attribute.key.to_string();
+        if META_ARGUMENTS.contains(&key.as_str()) {
+            continue;
+        }
+        let (value, pending) = expression(&attribute.value);
+        values.insert(key.clone(), value);
+        if let Some(pending) = pending {
+            unknown.insert(key, pending);
+        }
+    }
+
+    for block in body.blocks() {
+        let ident = block.ident.to_string();
+        match ident.as_str() {
+            name if META_BLOCKS.contains(&name) => {}
+            // The blocks a `dynamic` block generates depend on its `for_each`, so it
+            // becomes one unknown element next to the static blocks of the same name
+            "dynamic" => {
+                if let Some(label) = block.labels.first() {
+                    push_block(&mut values, &mut unknown, label.as_str(), Value::Null, Value::Bool(true));
+                }
+            }
+            _ => {
+                let (block_values, block_unknown) = body_values(&block.body);
+                push_block(&mut values, &mut unknown, &ident, Value::Object(block_values), Value::Object(block_unknown));
+            }
+        }
+    }
+
+    (values, unknown)
+}
+
+/// Append a nested block to the lists of its name in both trees
+fn push_block(values: &mut Map<String, Value>, unknown: &mut Map<String, Value>, name: &str, value: Value, pending: Value) {
+    if let Some(list) = values.entry(name).or_insert_with(|| json!([])).as_array_mut() {
+        list.push(value);
+    }
+    if let Some(list) = unknown.entry(name).or_insert_with(|| json!([])).as_array_mut() {
+        list.push(pending);
+    }
+}
+
+/// A literal expression as JSON, with the unknown tree of the parts that are
+/// not literals (`None` when everything is known)
+fn expression(expression: &Expression) -> (Value, Option<Value>) {
+    match expression {
+        Expression::Null(_) => (Value::Null, None),
+        Expression::Bool(b) => (Value::Bool(*b.value()), None),
+        Expression::Number(n) => (number(n.value()), None),
+        Expression::String(s) => (Value::String(s.value().clone()), None),
+        Expression::Parenthesis(inner) => self::expression(inner.inner()),
+        // `from_port = -1` is an operation on the literal 1
+        Expression::UnaryOp(op) if op.operator.value() == &UnaryOperator::Neg => match &op.expr {
+            Expression::Number(n) => match number(n.value()) {
+                Value::Number(n) if n.is_i64() => (json!(-n.as_i64().unwrap_or_default()), None),
+                Value::Number(n) => (json!(-n.as_f64().unwrap_or_default()), None),
+                _ => (Value::Null, Some(Value::Bool(true))),
+            },
+            _ => (Value::Null, Some(Value::Bool(true))),
+        },
+        Expression::Array(array) => {
+            let (items, pending): (Vec<Value>, Vec<Option<Value>>) = array.iter().map(self::expression).unzip();
+            let unknown = pending.iter().any(Option::is_some)
+                .then(|| Value::Array(pending.into_iter().map(|p| p.unwrap_or(Value::Bool(false))).collect()));
+            (Value::Array(items), unknown)
+        }
+        Expression::Object(object) => {
+            let mut values = Map::new();
+            let mut unknown = HashMap::new();
+            for (key, value) in object.iter() {
+                let key = match key {
+                    ObjectKey::Ident(ident) => ident.to_string(),
+                    ObjectKey::Expression(Expression::String(s)) => s.value().clone(),
+                    // Computed keys make the whole object unknown
+                    ObjectKey::Expression(_) => return (Value::Null, Some(Value::Bool(true))),
+                };
+                let (value, pending) = self::expression(value.expr());
+                values.insert(key.clone(), value);
+                if let Some(pending) = pending {
+                    unknown.insert(key, pending);
+                }
+            }
+            let unknown = (!unknown.is_empty()).then(|| Value::Object(unknown.into_iter().collect()));
+            (Value::Object(values), unknown)
+        }
+        // Interpolations, references, function calls, conditionals and for expressions
+        _ => (Value::Null, Some(Value::Bool(true))),
+    }
+}
+
+fn number(number: &Number) -> Value {
+    number.as_i64().map(Value::from)
+        .or_else(|| number.as_f64().map(Value::from))
+        .unwrap_or(Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_source() {
+        let resources = parse_source(r#"
+variable "office_cidr" {}
+
+resource "aws_security_group" "web" {
+  name = "web"
+
+  ingress {
+    protocol    = "tcp"
+    from_port   = 22
+    to_port     = 22
+    cidr_blocks = ["0.0.0.0/0", var.office_cidr]
+  }
+
+  ingress {
+    protocol  = "icmp"
+    from_port = -1
+    to_port   = -1
+  }
+
+  dynamic "egress" {
+    for_each = var.egress
+    content {}
+  }
+
+  tags = { Name = "web" }
+}
+
+  # terraguard:ignore=TG-SG-001,TG-SG-004 reason="Public load balancer"
+  resource "aws_security_group_rule" "https" {
+    security_group_id = aws_security_group.web.id
+  }
+"#, "main.tf").unwrap();
+
+        assert_eq!(resources.len(), 2);
+        let group = &resources[0];
+        assert_eq!(group.address, "aws_security_group.web");
+        assert_eq!(group.location, SourceLocation { file: "main.tf".to_string(), line: 4, column: 1 });
+        assert_eq!(group.values["ingress"][0]["cidr_blocks"], json!(["0.0.0.0/0", null]));
+        assert_eq!(group.unknown["ingress"][0], json!({ "cidr_blocks": [false, true] }));
+        assert_eq!(group.values["ingress"][1]["from_port"], json!(-1));
+        assert_eq!(group.unknown["egress"], json!([true]));
+        assert_eq!(group.values["tags"], json!({ "Name": "web" }));
+
+        let rule = &resources[1];
+        assert_eq!(rule.location.line, 29);
+        assert_eq!(rule.location.column, 3);
+        assert_eq!(rule.references["security_group_id"], "aws_security_group.web.id");
+        assert_eq!(rule.suppressions.len(), 2);
+        assert_eq!(rule.suppressions[1].rule, "TG-SG-004");
+        assert_eq!(rule.suppressions[1].reason, "Public load balancer");
+        assert_eq!(rule.suppressions[1].location.line, 28);
+        assert!(group.suppressions.is_empty());
+
+        let unexplained = "resource \"aws_instance\" \"web\" { # terraguard:ignore=TG-EC2-001\n}";
+        assert!(parse_source(unexplained, "main.tf").unwrap_err().contains("has no reason"));
+
+        assert!(parse_source("resource \"aws_instance\" {", "broken.tf").is_err());
+    }
+
+    #[test]
+    fn test_static_and_dynamic_blocks() {
+        let resources = parse_source(r#"
+resource "aws_security_group" "web" {
+  tags = { Name = "web" }
+
+  ingress {
+    protocol    = "tcp"
+    from_port   = 22
+    to_port     = 22
+    cidr_blocks = ["0.0.0.0/0"]
+  }
+
+  dynamic "ingress" {
+    for_each = var.extra_rules
+    content {}
+  }
+}
+"#, "main.tf").unwrap();
+
+        let group = &resources[0];
+        assert_eq!(group.unknown["ingress"], json!([{}, true]));
+        assert_eq!(group.values["ingress"][0]["from_port"], json!(22));
+
+        // The static rule is still judged; only the dynamic one is undeterminable
+        let issues = crate::rules::FastValidator::new().validate_source(&resources);
+        let rules: Vec<&str> = issues.iter().map(|issue| issue.rule_id.as_str()).collect();
+        assert!(rules.contains(&"TG-SG-001"));
+        assert!(rules.contains(&"TG-SG-003"));
+        let unknown: Vec<_> = issues.iter().filter(|issue| issue.rule_id == "TG-PLAN-001").collect();
+        assert_eq!(unknown.len(), 1);
+        assert_eq!(unknown[0].attributes["attribute"], "ingress.1");
+        assert!(unknown[0].message.contains("from source; it is set by a variable"));
+    }
+}
@@ -0,0 +1,188 @@
+use crate::cloudformation::{is_template, parse_template};
+use crate::suppression::glob_match;
+use serde_json::Value;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// Input argument that reads from standard input
+pub const STDIN: &str = "-";
+
+/// A plan, state or template read from one input
+#[derive(Debug, Clone)]
+pub struct InputDocument {
+    // The file it was read from, or `<stdin>`; `#2`, `#3`, ... mark further
+    // documents of a stream, as written by `terragrunt run-all show -json`
+    pub source: String,
+    pub document: Value,
+}
+
+/// Read every document named by the `INPUT` arguments, in order. Arguments may be
+/// files, `-` for stdin, globs (`*`, `?`, and `**` for any number of directories),
+/// or directories, which are searched recursively for `.json` files. Files found
+/// in directories that are not plans, states or templates are skipped, as are
+/// hidden directories such as `.terraform` and `.terragrunt-cache`.
+pub fn load_inputs(inputs: &[String]) -> Result<Vec<InputDocument>, String> {
+    let mut documents = Vec::new();
+    for input in inputs {
+        if input == STDIN {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text)
+                .map_err(|e| format!("could not read stdin: {}", e))?;
+            documents.extend(parse_documents(&text, "<stdin>")?);
+        } else if Path::new(input).is_dir() {
+            for file in json_files(Path::new(input))? {
+                let source = file.to_string_lossy();
+                let Ok(text) = fs::read_to_string(&file) else {
+                    continue;
+                };
+                let found = parse_documents(&text, &source).unwrap_or_default();
+                documents.extend(found.into_iter().filter(|d| is_validatable(&d.document)));
+            }
+        } else if input.contains(['*', '?']) {
+            let matches = expand_glob(input);
+            if matches.is_empty() {
+                return Err(format!("no files match {}", input));
+            }
+            for file in matches.iter().filter(|path| path.is_file()) {
+                documents.extend(read_file(&file.to_string_lossy())?);
+            }
+        } else {
+            documents.extend(read_file(input)?);
+        }
+    }
+    Ok(documents)
+}
+
+fn read_file(path: &str) -> Result<Vec<InputDocument>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("could not read {}: {}", path, e))?;
+    parse_documents(&text, path)
+}
+
+/// Parse one or more concatenated JSON documents, or a YAML CloudFormation template
+fn parse_documents(text: &str, source: &str) -> Result<Vec<InputDocument>, String> {
+    let parsed: Result<Vec<Value>, _> = serde_json::Deserializer::from_str(text).into_iter::<Value>().collect();
+    let documents = match parsed {
+        Ok(documents) if !documents.is_empty() => documents,
+        // CloudFormation templates are often written in YAML
+        _ => match parse_template(text) {
+            Ok(template) if is_template(&template) => vec![template],
+            _ => return Err(format!("could not parse JSON from {}", source)),
+        },
+    };
+
+    let several = documents.len() > 1;
+    Ok(documents.into_iter().enumerate().map(|(index, document)| InputDocument {
+        source: match (several, index) {
+            (true, index) if index > 0 => format!("{}#{}", source, index + 1),
+            _ => source.to_string(),
+        },
+        document,
+    }).collect())
+}
+
+/// Whether a document is a Terraform/OpenTofu plan or state, or a CloudFormation template
+fn is_validatable(document: &Value) -> bool {
+    ["format_version", "resource_changes", "planned_values", "values", "resources"].iter()
+        .any(|key| document.get(key).is_some())
+        || is_template(document)
+}
+
+/// Every `.json` file under `dir`, in a stable order, skipping hidden directories
+fn json_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    for entry in sorted_entries(dir)? {
+        if entry.is_dir() {
+            if !is_hidden(&entry) {
+                files.extend(json_files(&entry)?);
+            }
+        } else if entry.extension().is_some_and(|ext| ext == "json") {
+            files.push(entry);
+        }
+    }
+    Ok(files)
+}
+
+fn sorted_entries(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let listed = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+    let mut entries: Vec<PathBuf> = fs::read_dir(listed)
+        .map_err(|e| format!("could not read directory {}: {}", listed.display(), e))?
+        .filter_map(|entry| entry.ok().map(|entry| dir.join(entry.file_name())))
+        .collect();
+    entries.sort();
+    Ok(entries)
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+/// Paths matching a glob, one path component at a time. Hidden entries only
+/// match components that start with a dot.
+fn expand_glob(pattern: &str) -> Vec<PathBuf> {
+    let root = if pattern.starts_with('/') { PathBuf::from("/") } else { PathBuf::new() };
+    let mut paths = vec![root];
+
+    for component in pattern.split('/').filter(|c| !c.is_empty() && *c != ".") {
+        paths = paths.into_iter().flat_map(|base| -> Vec<PathBuf> {
+            if component == "**" {
+                let mut dirs = vec![base.clone()];
+                dirs.extend(subdirectories(&base));
+                return dirs;
+            }
+            if !component.contains(['*', '?']) {
+                let path = base.join(component);
+                return if path.exists() { vec![path] } else { Vec::new() };
+            }
+            sorted_entries(&base).unwrap_or_default().into_iter()
+                .filter(|path| !is_hidden(path) || component.starts_with('.'))
+                .filter(|path| path.file_name().is_some_and(|name| glob_match(component, &name.to_string_lossy())))
+                .collect()
+        }).collect();
+    }
+
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+/// Every non-hidden directory below `dir`
+fn subdirectories(dir: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    for entry in sorted_entries(dir).unwrap_or_default() {
+        if entry.is_dir() && !is_hidden(&entry) {
+            dirs.push(entry.clone());
+            dirs.extend(subdirectories(&entry));
+        }
+    }
+    dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terragrunt_stream_and_directories() {
+        let documents = parse_documents(r#"{"format_version": "1.2", "resource_changes": []}
+{"format_version": "1.2", "terraform_version": "1.8.0", "resource_changes": []}"#, "<stdin>").unwrap();
+        let sources: Vec<&str> = documents.iter().map(|d| d.source.as_str()).collect();
+        assert_eq!(sources, ["<stdin>", "<stdin>#2"]);
+
+        let dir = std::env::temp_dir().join(format!("terraguard-inputs-{}", std::process::id()));
+        fs::create_dir_all(dir.join("live/app/.terragrunt-cache")).unwrap();
+        fs::write(dir.join("live/app/tfplan.json"), r#"{"format_version": "1.2"}"#).unwrap();
+        fs::write(dir.join("live/app/.terragrunt-cache/tfplan.json"), r#"{"format_version": "1.2"}"#).unwrap();
+        fs::write(dir.join("live/package.json"), r#"{"name": "tooling"}"#).unwrap();
+
+        let found = load_inputs(&[dir.join("live").to_string_lossy().into_owned()]).unwrap();
+        assert_eq!(found.len(), 1);
+        assert!(found[0].source.ends_with("app/tfplan.json"));
+
+        let globbed = expand_glob(&format!("{}/**/*.json", dir.display()));
+        assert_eq!(globbed.len(), 2);
+        assert!(load_inputs(&[format!("{}/*.yaml", dir.display())]).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,35 @@
+//! TerraGuard: security checks for Terraform plans, Terraform sources and
+//! CloudFormation templates.
+//!
+//! The `tg` and `terra` binaries are thin wrappers around this library. To run
+//! the built-in checks with a custom port catalog:
+//!
+//! ```no_run
+//! use terraguard::ports::PortCatalog;
+//! use terraguard::protocol::ProtocolPort;
+//! use terraguard::rules::{FastValidator, ValidatorConfig};
+//!
+//! let mut ports = PortCatalog::default();
+//! ports.allow(ProtocolPort::tcp(9443));
+//! ports.disallow(ProtocolPort::tcp(9200));
+//! ports.name_service(ProtocolPort::tcp(9200), "Elasticsearch");
+//!
+//! let plan = serde_json::json!({ "resource_changes": [] });
+//! let issues = FastValidator::with_config(ValidatorConfig { ports }).validate(&plan);
+//! ```
+
+pub mod baseline;
+pub mod catalog;
+pub mod cidr;
+pub mod cloudformation;
+pub mod config;
+pub mod hcl;
+pub mod inputs;
+pub mod monitor;
+pub mod policy;
+pub mod ports;
+pub mod protocol;
+pub mod report;
+pub mod rules;
+pub mod suppression;
+pub mod types;
@@ -1,30 +1,176 @@
-mod rules;
-mod types;
-mod report;
-mod monitor;
-
-use crate::report::print_report;
-use crate::monitor::{monitor_resources, display_metrics};
-use clap::{Parser, Subcommand};
-use serde_json::Value;
-use std::fs;
+use terraguard::hcl::load_sources;
+use terraguard::inputs::{load_inputs, InputDocument};
+use terraguard::config::{ConfigFile, Settings};
+use terraguard::baseline::{Baseline, BaselineOutcome, DEFAULT_BASELINE_FILE};
+use terraguard::report::{collapse_instances, write_report, OutputFormat, ReportData};
+use terraguard::monitor::{monitor_resources, display_metrics, DriftMonitor};
+use terraguard::policy::PolicySet;
+use terraguard::ports::{PortCatalog, PortCatalogFile};
+use terraguard::rules::{FastValidator, ValidatorConfig};
+use terraguard::suppression::{apply_inline, InlineSuppression, Suppressions};
+use terraguard::types::{state_resources, ChangeStatus, Issue, Severity};
+use chrono::Utc;
+use clap::{Args, Parser, Subcommand};
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+use std::env;
+use std::path::{Path, PathBuf};
 use std::process;
 
+// Exit codes for `validate`
+const EXIT_CLEAN: i32 = 0;
+const EXIT_FINDINGS: i32 = 1;
+const EXIT_ERROR: i32 = 2;
+
 #[derive(Parser)]
 #[clap(name = "terraguard", about = "Lightweight, blazing-fast Rust CLI tool to detect Terraform security drifts")]
 struct Cli {
+    /// Configuration file to use instead of discovering .terraguard.toml
+    #[clap(long, global = true)]
+    config: Option<String>,
+
     #[clap(subcommand)]
     command: Commands,
 }
 
+/// Options selecting the port catalog used by the built-in checks
+#[derive(Args, Clone, Default)]
+struct CatalogArgs {
+    /// Port catalog file (YAML or JSON) with allowed, disallowed and named ports
+    #[clap(long)]
+    ports: Option<String>,
+
+    /// Apply the port catalog overrides for this environment
+    #[clap(long)]
+    env: Option<String>,
+
+    /// Apply the port catalog overrides for this application
+    #[clap(long)]
+    app: Option<String>,
+}
+
+/// Options that can also be set in .terraguard.toml; flags take precedence
+#[derive(Args, Clone, Default)]
+struct SettingsArgs {
+    /// Policy file (YAML or JSON) with custom security rules; may be repeated
+    #[clap(long, short = 'p')]
+    policy: Vec<String>,
+
+    /// Report format [default: text]
+    #[clap(long, short = 'f', value_enum)]
+    format: Option<OutputFormat>,
+
+    /// Exit with a non-zero code when an issue of this severity or higher is found
+    #[clap(long, value_enum)]
+    fail_on: Option<Severity>,
+
+    /// Suppression file with justified, expiring exceptions [default: .terraguard-ignore if present]
+    #[clap(long)]
+    ignore_file: Option<String>,
+
+    /// Report identical findings on count/for_each instances once, as address[*]
+    #[clap(long)]
+    collapse_instances: bool,
+
+    #[clap(flatten)]
+    catalog: CatalogArgs,
+}
+
+#[derive(Args)]
+struct ValidateArgs {
+    /// Plan JSON files, directories, globs, or - for stdin; state files with --state
+    #[clap(name = "INPUT", required = true)]
+    inputs: Vec<String>,
+
+    /// Audit deployed resources: INPUT is a terraform.tfstate (v4) or `terraform show -json` of a state
+    #[clap(long)]
+    state: bool,
+
+    #[clap(flatten)]
+    settings: SettingsArgs,
+
+    /// Write the report to this file instead of stdout
+    #[clap(long, short = 'o')]
+    output: Option<String>,
+
+    /// Only report findings that are not part of this baseline file
+    #[clap(long)]
+    baseline: Option<String>,
+
+    /// Print a coverage summary, including resource types without checks, to stderr
+    #[clap(long, short = 'v')]
+    verbose: bool,
+}
+
+#[derive(Args)]
+struct ScanArgs {
+    /// Directory (searched recursively) or .tf file to scan
+    #[clap(name = "DIR")]
+    dir: String,
+
+    #[clap(flatten)]
+    settings: SettingsArgs,
+
+    /// Write the report to this file instead of stdout
+    #[clap(long, short = 'o')]
+    output: Option<String>,
+
+    /// Print a coverage summary, including resource types without checks, to stderr
+    #[clap(long, short = 'v')]
+    verbose: bool,
+}
+
+#[derive(Subcommand)]
+enum BaselineCommands {
+    /// Record every current finding in a baseline file
+    #[clap(name = "create")]
+    Create {
+        /// Input Terraform plan JSON file, or - for stdin
+        #[clap(name = "INPUT")]
+        input: String,
+
+        /// Policy file (YAML or JSON) with custom security rules; may be repeated
+        #[clap(long, short = 'p')]
+        policy: Vec<String>,
+
+        /// Baseline file to write
+        #[clap(long, short = 'o', default_value = DEFAULT_BASELINE_FILE)]
+        output: String,
+
+        #[clap(flatten)]
+        catalog: CatalogArgs,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Print the effective configuration after merging flags, .terraguard.toml and defaults
+    #[clap(name = "show")]
+    Show(SettingsArgs),
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Validate a Terraform plan for security issues
     #[clap(name = "validate")]
-    Validate {
-        /// Input Terraform plan JSON file
-        #[clap(name = "INPUT")]
-        input: String,
+    Validate(ValidateArgs),
+
+    /// Scan Terraform .tf sources without running terraform plan
+    #[clap(name = "scan")]
+    Scan(ScanArgs),
+
+    /// Manage baselines of accepted findings
+    #[clap(name = "baseline")]
+    Baseline {
+        #[clap(subcommand)]
+        command: BaselineCommands,
+    },
+
+    /// Inspect the project configuration
+    #[clap(name = "config")]
+    Config {
+        #[clap(subcommand)]
+        command: ConfigCommands,
     },
     
     /// Run continuous security monitoring
@@ -41,6 +187,14 @@ enum Commands {
         /// Interval in seconds between checks
         #[clap(long, short = 'i', default_value = "60")]
         interval: u64,
+
+        /// Port catalog file; its application overrides follow the resource mapping
+        #[clap(long)]
+        ports: Option<String>,
+
+        /// Apply the port catalog overrides for this environment
+        #[clap(long)]
+        env: Option<String>,
     },
     
     /// Display the latest metrics for all applications
@@ -60,11 +214,27 @@ fn main() {
     let cli = Cli::parse();
     
     match cli.command {
-        Commands::Validate { input } => {
-            run_validation(input);
+        Commands::Validate(args) => {
+            process::exit(run_validation(cli.config.as_deref(), &args));
+        },
+        Commands::Scan(args) => {
+            process::exit(run_scan(cli.config.as_deref(), &args));
+        },
+        Commands::Baseline { command: BaselineCommands::Create { input, policy, output, catalog } } => {
+            let settings = SettingsArgs { policy, catalog, ..Default::default() };
+            process::exit(run_baseline_create(cli.config.as_deref(), &input, &settings, &output));
         },
-        Commands::Monitor { directory, mapping, interval } => {
-            monitor_resources(&directory, &mapping, interval);
+        Commands::Config { command: ConfigCommands::Show(args) } => {
+            process::exit(run_config_show(cli.config.as_deref(), &args));
+        },
+        Commands::Monitor { directory, mapping, interval, ports, env } => {
+            match load_monitor(cli.config.as_deref(), ports, env) {
+                Ok(monitor) => monitor_resources(&directory, &mapping, interval, monitor),
+                Err(e) => {
+                    eprintln!("❌ Error loading configuration: {}", e);
+                    process::exit(EXIT_ERROR);
+                }
+            }
         },
         Commands::Metrics { application, format } => {
             display_metrics(application, &format);
@@ -72,17 +242,358 @@ fn main() {
     }
 }
 
-fn run_validation(input: String) {
-    let data = fs::read_to_string(&input).unwrap_or_else(|_| {
-        eprintln!("❌ Error reading file: {}", &input);
-        process::exit(1);
-    });
-    
-    let plan: Value = serde_json::from_str(&data).unwrap_or_else(|_| {
-        eprintln!("❌ Error parsing JSON from file: {}", &input);
-        process::exit(1);
-    });
-    
-    let issues = rules::fast_validate(&plan);
-    print_report(&issues);
-}
\ No newline at end of file
+/// Read every plan, state or template named by the inputs, printing the reason on failure
+fn load_documents(inputs: &[String]) -> Option<Vec<InputDocument>> {
+    match load_inputs(inputs) {
+        Ok(documents) if !documents.is_empty() => Some(documents),
+        Ok(_) => {
+            eprintln!("❌ Error reading input: no plans found in {}", inputs.join(", "));
+            None
+        }
+        Err(e) => {
+            eprintln!("❌ Error reading input: {}", e);
+            None
+        }
+    }
+}
+
+/// Load the explicitly given config file, or the nearest .terraguard.toml
+fn load_config_file(config_path: Option<&str>) -> Result<Option<(PathBuf, ConfigFile)>, String> {
+    match config_path {
+        Some(path) => ConfigFile::load(Path::new(path)).map(|config| Some((PathBuf::from(path), config))),
+        None => {
+            let cwd = env::current_dir().map_err(|e| format!("could not determine working directory: {}", e))?;
+            ConfigFile::discover(&cwd)
+        }
+    }
+}
+
+/// Merge CLI flags, the config file and defaults, in that order of precedence
+fn resolve_settings(config_path: Option<&str>, args: &SettingsArgs) -> Result<Settings, String> {
+    let (config_file, config) = match load_config_file(config_path)? {
+        Some((path, config)) => (Some(path), config),
+        None => (None, ConfigFile::default()),
+    };
+
+    let environment = args.catalog.env.clone().or(config.environment);
+    let application = args.catalog.app.clone().or(config.application);
+    let catalog = match &args.catalog.ports {
+        Some(path) => Some(PortCatalogFile::load(path)?),
+        None => config.ports,
+    };
+    let ports = match catalog {
+        Some(catalog) => catalog.resolve(environment.as_deref(), application.as_deref())?,
+        None => PortCatalog::default(),
+    };
+
+    Ok(Settings {
+        config_file,
+        policies: if args.policy.is_empty() { config.policies } else { args.policy.clone() },
+        ignore_file: args.ignore_file.clone().or(config.ignore_file),
+        format: args.format.or(config.format).unwrap_or(OutputFormat::Text),
+        fail_on: args.fail_on.clone().or(config.fail_on),
+        collapse_instances: args.collapse_instances || config.collapse_instances.unwrap_or(false),
+        environment,
+        application,
+        rules: config.rules,
+        ports,
+    })
+}
+
+/// Build the drift monitor, taking the port catalog from the flags or the config file
+fn load_monitor(config_path: Option<&str>, ports: Option<String>, environment: Option<String>) -> Result<DriftMonitor, String> {
+    let config = load_config_file(config_path)?.map(|(_, config)| config).unwrap_or_default();
+    let catalog = match ports {
+        Some(path) => Some(PortCatalogFile::load(&path)?),
+        None => config.ports,
+    };
+
+    match catalog {
+        Some(catalog) => DriftMonitor::with_port_catalog(catalog, environment.or(config.environment)),
+        None => Ok(DriftMonitor::new()),
+    }
+}
+
+/// Run the built-in checks and policy rules against a plan, then apply the
+/// rule settings. Returns the current issues, the issues fixed by the planned
+/// updates, and the validator, which knows what was and wasn't checked and
+/// holds the warnings about the input.
+fn scan_plan(input: &InputDocument, policies: &PolicySet, settings: &Settings) -> (Vec<Issue>, Vec<Issue>, FastValidator) {
+    let mut validator = new_validator(settings);
+    let mut issues = validator.validate(&input.document);
+    issues.extend(policies.evaluate(&input.document));
+
+    let (fixed, issues): (Vec<Issue>, Vec<Issue>) = settings.rules.apply(issues).into_iter()
+        .map(|issue| issue.with_source(&input.source))
+        .partition(|issue| issue.change_status == Some(ChangeStatus::Fixed));
+    (issues, fixed, validator)
+}
+
+/// Run the built-in checks and policy rules against the resources recorded in
+/// a Terraform state, then apply the rule settings
+fn scan_state(input: &InputDocument, policies: &PolicySet, settings: &Settings) -> Result<(Vec<Issue>, FastValidator), String> {
+    let resources = state_resources(&input.document).map_err(|e| format!("{}: {}", input.source, e))?;
+    let mut validator = new_validator(settings);
+    let mut issues = validator.validate_resources(&resources);
+    issues.extend(policies.evaluate_resources(&resources));
+
+    let issues = settings.rules.apply(issues).into_iter().map(|issue| issue.with_source(&input.source)).collect();
+    Ok((issues, validator))
+}
+
+/// Run the built-in checks and policy rules against the literal values of the
+/// `.tf` files under `path`, then apply the sources' `terraguard:ignore`
+/// comments and the rule settings. Returns the issues still reported, the inline
+/// suppressions with what they hid, and the validator.
+fn scan_source(path: &str, policies: &PolicySet, settings: &Settings) -> Result<(Vec<Issue>, Vec<InlineSuppression>, FastValidator), String> {
+    let sources = load_sources(Path::new(path))?;
+    let mut validator = new_validator(settings);
+    let mut issues = validator.validate_source(&sources);
+
+    issues.extend(policies.evaluate_source(&sources));
+
+    // Before the rule settings, so a comment for a disabled rule still counts as used
+    let inline = sources.into_iter().flat_map(|source| source.suppressions).collect();
+    let (issues, inline) = apply_inline(issues, inline);
+    Ok((settings.rules.apply(issues), inline, validator))
+}
+
+fn new_validator(settings: &Settings) -> FastValidator {
+    FastValidator::with_config(ValidatorConfig { ports: settings.ports.clone() })
+}
+
+/// Print which resources were checked and which types have no checks
+fn print_coverage(checked: usize, unknown: &BTreeMap<String, usize>) {
+    eprintln!("ℹ️  Checked {} resource(s)", checked);
+
+    if !unknown.is_empty() {
+        eprintln!("ℹ️  No built-in checks for {} resource type(s):", unknown.len());
+        for (resource_type, count) in unknown {
+            eprintln!("   - {} ({})", resource_type, count);
+        }
+    }
+}
+
+/// Validate the inputs into one report and return the process exit code:
+/// `EXIT_FINDINGS` when an issue reaches the `--fail-on` threshold,
+/// `EXIT_ERROR` on input problems
+fn run_validation(config_path: Option<&str>, args: &ValidateArgs) -> i32 {
+    let input = args.inputs.join(", ");
+    let settings = match resolve_settings(config_path, &args.settings) {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("❌ Error loading configuration: {}", e);
+            return EXIT_ERROR;
+        }
+    };
+
+    let policies = match PolicySet::load_all(&settings.policies) {
+        Ok(policies) => policies,
+        Err(e) => {
+            eprintln!("❌ Error loading policy: {}", e);
+            return EXIT_ERROR;
+        }
+    };
+
+    let suppressions = match Suppressions::discover(settings.ignore_file.as_deref()) {
+        Ok(suppressions) => suppressions,
+        Err(e) => {
+            eprintln!("❌ Error loading suppressions: {}", e);
+            return EXIT_ERROR;
+        }
+    };
+
+    let baseline = match args.baseline.as_deref().map(Baseline::load).transpose() {
+        Ok(baseline) => baseline,
+        Err(e) => {
+            eprintln!("❌ Error loading baseline: {}", e);
+            return EXIT_ERROR;
+        }
+    };
+
+    let documents = match load_documents(&args.inputs) {
+        Some(documents) => documents,
+        None => return EXIT_ERROR,
+    };
+
+    // Inputs are validated in parallel, each with its own validator so equal
+    // addresses in different units are all checked, and merged in input order
+    let scanned: Vec<_> = documents.par_iter()
+        .map(|document| if args.state {
+            scan_state(document, &policies, &settings).map(|(found, validator)| (found, Vec::new(), validator))
+        } else {
+            Ok(scan_plan(document, &policies, &settings))
+        })
+        .collect();
+
+    let (mut issues, mut fixed) = (Vec::new(), Vec::new());
+    let mut resources = Vec::new();
+    let mut unknown_types: BTreeMap<String, usize> = BTreeMap::new();
+    for (document, result) in documents.iter().zip(scanned) {
+        let (found, found_fixed, validator) = match result {
+            Ok(scanned) => scanned,
+            Err(e) => {
+                eprintln!("❌ Error reading state: {}", e);
+                return EXIT_ERROR;
+            }
+        };
+        for warning in validator.warnings() {
+            eprintln!("⚠️  {}: {}", document.source, warning);
+        }
+        issues.extend(found);
+        fixed.extend(found_fixed);
+        resources.extend(validator.checked_resources().iter().map(|address| (document.source.clone(), address.clone())));
+        for (resource_type, count) in validator.unknown_types() {
+            *unknown_types.entry(resource_type.clone()).or_insert(0) += count;
+        }
+    }
+    if args.verbose {
+        print_coverage(resources.len(), &unknown_types);
+    }
+
+    // Drop known debt before applying exceptions, so stale entries are judged on every finding
+    let outcome = match &baseline {
+        Some(baseline) => baseline.apply(issues),
+        None => BaselineOutcome { new_issues: issues, ..Default::default() },
+    };
+    let (issues, suppressed) = suppressions.apply(outcome.new_issues, Utc::now().date_naive());
+    let issues = if settings.collapse_instances { collapse_instances(issues) } else { issues };
+
+    let data = ReportData {
+        input: &input,
+        issues: &issues,
+        suppressed: &suppressed,
+        inline_suppressions: &[],
+        resources: &resources,
+        baselined: outcome.baselined,
+        stale_baseline: &outcome.stale,
+        fixed: &fixed,
+    };
+    if let Err(e) = write_report(settings.format, &data, args.output.as_deref()) {
+        eprintln!("❌ Error writing report: {}", e);
+        return EXIT_ERROR;
+    }
+
+    match &settings.fail_on {
+        Some(threshold) if issues.iter().any(|i| i.severity >= *threshold) => EXIT_FINDINGS,
+        _ => EXIT_CLEAN,
+    }
+}
+
+/// Scan `.tf` sources and return the process exit code, as `run_validation` does
+fn run_scan(config_path: Option<&str>, args: &ScanArgs) -> i32 {
+    let settings = match resolve_settings(config_path, &args.settings) {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("❌ Error loading configuration: {}", e);
+            return EXIT_ERROR;
+        }
+    };
+
+    let policies = match PolicySet::load_all(&settings.policies) {
+        Ok(policies) => policies,
+        Err(e) => {
+            eprintln!("❌ Error loading policy: {}", e);
+            return EXIT_ERROR;
+        }
+    };
+
+    let suppressions = match Suppressions::discover(settings.ignore_file.as_deref()) {
+        Ok(suppressions) => suppressions,
+        Err(e) => {
+            eprintln!("❌ Error loading suppressions: {}", e);
+            return EXIT_ERROR;
+        }
+    };
+
+    let (issues, inline, validator) = match scan_source(&args.dir, &policies, &settings) {
+        Ok(scanned) => scanned,
+        Err(e) => {
+            eprintln!("❌ Error reading sources: {}", e);
+            return EXIT_ERROR;
+        }
+    };
+    if args.verbose {
+        print_coverage(validator.checked_resources().len(), validator.unknown_types());
+    }
+
+    let (issues, suppressed) = suppressions.apply(issues, Utc::now().date_naive());
+    let resources: Vec<_> = validator.checked_resources().iter()
+        .map(|address| (args.dir.clone(), address.clone()))
+        .collect();
+    let data = ReportData {
+        input: &args.dir,
+        issues: &issues,
+        suppressed: &suppressed,
+        inline_suppressions: &inline,
+        resources: &resources,
+        baselined: 0,
+        stale_baseline: &[],
+        fixed: &[],
+    };
+    if let Err(e) = write_report(settings.format, &data, args.output.as_deref()) {
+        eprintln!("❌ Error writing report: {}", e);
+        return EXIT_ERROR;
+    }
+
+    match &settings.fail_on {
+        Some(threshold) if issues.iter().any(|i| i.severity >= *threshold) => EXIT_FINDINGS,
+        _ => EXIT_CLEAN,
+    }
+}
+
+/// Write a baseline containing every finding currently reported for a plan
+fn run_baseline_create(config_path: Option<&str>, input: &str, args: &SettingsArgs, output: &str) -> i32 {
+    let settings = match resolve_settings(config_path, args) {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("❌ Error loading configuration: {}", e);
+            return EXIT_ERROR;
+        }
+    };
+
+    let policies = match PolicySet::load_all(&settings.policies) {
+        Ok(policies) => policies,
+        Err(e) => {
+            eprintln!("❌ Error loading policy: {}", e);
+            return EXIT_ERROR;
+        }
+    };
+
+    let documents = match load_documents(&[input.to_string()]) {
+        Some(documents) => documents,
+        None => return EXIT_ERROR,
+    };
+
+    let mut issues = Vec::new();
+    for document in &documents {
+        let (found, _, validator) = scan_plan(document, &policies, &settings);
+        for warning in validator.warnings() {
+            eprintln!("⚠️  {}: {}", document.source, warning);
+        }
+        issues.extend(found);
+    }
+    let baseline = Baseline::from_issues(&issues);
+    if let Err(e) = baseline.save(output) {
+        eprintln!("❌ Error writing baseline: {}", e);
+        return EXIT_ERROR;
+    }
+
+    println!("✅ Baseline with {} finding(s) written to {}", baseline.entries.len(), output);
+    EXIT_CLEAN
+}
+
+/// Print the configuration `validate` would run with for the same flags
+fn run_config_show(config_path: Option<&str>, args: &SettingsArgs) -> i32 {
+    let rendered = resolve_settings(config_path, args).and_then(|settings| settings.to_toml());
+    match rendered {
+        Ok(rendered) => {
+            print!("{}", rendered);
+            EXIT_CLEAN
+        }
+        Err(e) => {
+            eprintln!("❌ Error loading configuration: {}", e);
+            EXIT_ERROR
+        }
+    }
+}
@@ -1,5 +1,3 @@
-#[warn(unused_imports)]
-use clap::{Parser, Subcommand};
 use colored::*;
 use std::fs;
 use std::path::Path;
@@ -10,7 +8,8 @@ use std::collections::HashMap;
 use crate::types::Severity;
 use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
-use crate::rules::FastValidator;
+use crate::ports::PortCatalogFile;
+use crate::rules::{FastValidator, ResourceContext, ValidatorConfig};
 use crate::types::Issue;
 
 // === Monitor structure and implementation ===
@@ -90,6 +89,10 @@ pub struct DriftMonitor {
     resource_to_app: HashMap<String, String>,
     // Validator to check for security issues
     validator: FastValidator,
+    // Port catalog and environment used to build per-application validators
+    port_catalog: Option<(PortCatalogFile, Option<String>)>,
+    // Validators for applications with their own port catalog overrides
+    app_validators: HashMap<String, FastValidator>,
     // Latest drift events
     recent_drifts: Vec<DriftEvent>,
     // Maximum events to keep in history
@@ -104,14 +107,37 @@ impl DriftMonitor {
             application_metrics: HashMap::new(),
             resource_to_app: HashMap::new(),
             validator: FastValidator::new(),
+            port_catalog: None,
+            app_validators: HashMap::new(),
             recent_drifts: Vec::new(),
             max_history_events: 100,
         }
     }
 
+    // Create a drift monitor that checks ports against a catalog file, applying
+    // its environment overrides everywhere and application overrides per application
+    pub fn with_port_catalog(catalog: PortCatalogFile, environment: Option<String>) -> Result<Self, String> {
+        let ports = catalog.resolve(environment.as_deref(), None)?;
+        Ok(Self {
+            validator: FastValidator::with_config(ValidatorConfig { ports }),
+            port_catalog: Some((catalog, environment)),
+            ..Self::new()
+        })
+    }
+
     // Map a resource to an application
     pub fn map_resource_to_app(&mut self, resource_id: &str, application: &str) {
         self.resource_to_app.insert(resource_id.to_string(), application.to_string());
+
+        // Applications with their own port overrides get their own validator
+        if let Some((catalog, environment)) = &self.port_catalog {
+            if catalog.applications.contains_key(application) && !self.app_validators.contains_key(application) {
+                // Overrides were already checked when the catalog file was loaded
+                if let Ok(ports) = catalog.resolve(environment.as_deref(), Some(application)) {
+                    self.app_validators.insert(application.to_string(), FastValidator::with_config(ValidatorConfig { ports }));
+                }
+            }
+        }
         
         // Ensure we have metrics for this application
         if !self.application_metrics.contains_key(application) {
@@ -166,19 +192,11 @@ impl DriftMonitor {
                     history.drift_count += 1;
                     history.last_modified = now;
     
-                    // Find new and resolved issues
+                    // Find new and resolved issues, keyed on fingerprints so that
+                    // reworded messages don't show up as drift
                     let previous_issues = &history.current_issues;
-                    let new_issues: Vec<Issue> = current_issues
-                        .iter()
-                        .filter(|i| !previous_issues.iter().any(|pi| pi.message == i.message))
-                        .cloned()
-                        .collect();
-    
-                    let resolved_issues: Vec<Issue> = previous_issues
-                        .iter()
-                        .filter(|i| !current_issues.iter().any(|ci| ci.message == i.message))
-                        .cloned()
-                        .collect();
+                    let new_issues = unmatched_issues(&current_issues, previous_issues);
+                    let resolved_issues = unmatched_issues(previous_issues, &current_issues);
     
                     // Only create drift event if there are actual security implications
                     if !new_issues.is_empty() || !resolved_issues.is_empty() {
@@ -254,10 +272,14 @@ impl DriftMonitor {
     
     // Validate a single resource and return issues
     fn validate_resource(&mut self, resource_id: &str, resource_type: &str, config: &Value) -> Vec<Issue> {
+        let validator = self.resource_to_app.get(resource_id)
+            .and_then(|app| self.app_validators.get(app))
+            .unwrap_or(&self.validator);
+
         match resource_type {
-            "security_group" => self.validator.check_security_group(resource_id.to_string(), config),
-            "ec2_instance" => self.validator.check_ec2_instance(resource_id.to_string(), config),
-            "s3_bucket" => self.validator.check_s3_bucket(resource_id.to_string(), config),
+            "security_group" => validator.check_security_group(&ResourceContext::new(resource_id, resource_type, config)),
+            "ec2_instance" => validator.check_ec2_instance(&ResourceContext::new(resource_id, resource_type, config)),
+            "s3_bucket" => validator.check_s3_bucket(&ResourceContext::new(resource_id, resource_type, config)),
             _ => Vec::new(), // Unknown resource type
         }
     }
@@ -291,6 +313,33 @@ impl DriftMonitor {
     }
 }
 
+impl Default for DriftMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Issues in `issues` that have no counterpart with the same fingerprint (rule,
+// resource and key attributes such as ports) in `other`. Each issue in `other`
+// can only be matched once, so a second identical finding still counts as new.
+fn unmatched_issues(issues: &[Issue], other: &[Issue]) -> Vec<Issue> {
+    let mut remaining: HashMap<String, usize> = HashMap::new();
+    for issue in other {
+        *remaining.entry(issue.fingerprint()).or_insert(0) += 1;
+    }
+
+    issues.iter()
+        .filter(|issue| match remaining.get_mut(&issue.fingerprint()) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        })
+        .cloned()
+        .collect()
+}
+
 // === CLI functionality for monitoring ===
 
 // Resource to application mapping
@@ -309,13 +358,12 @@ fn load_resource_mapping(path: &str) -> ResourceMapping {
 }
 
 // Monitor resources for security drifts
-pub fn monitor_resources(directory: &str, mapping_path: &str, interval: u64) {
+pub fn monitor_resources(directory: &str, mapping_path: &str, interval: u64, monitor: DriftMonitor) {
     println!("{}", "Starting continuous security drift monitoring...".blue().bold());
     println!("Monitoring directory: {}", directory);
     println!("Check interval: {} seconds", interval);
     
-    // Create drift monitor
-    let mut monitor = DriftMonitor::new();
+    let mut monitor = monitor;
     
     // Load resource to application mapping
     let mapping = load_resource_mapping(mapping_path);
@@ -395,8 +443,9 @@ fn process_resource_file(monitor: &mut DriftMonitor, path: &Path, drift_detected
                             if !drift_event.new_issues.is_empty() {
                                 println!("  {} New issues:", "•".red());
                                 for issue in &drift_event.new_issues {
-                                    println!("    - [{}] {}", 
+                                    println!("    - [{}] {}: {}", 
                                         format!("{:?}", issue.severity).red().bold(),
+                                        issue.rule_id,
                                         issue.message);
                                 }
                             }
@@ -405,8 +454,9 @@ fn process_resource_file(monitor: &mut DriftMonitor, path: &Path, drift_detected
                             if !drift_event.resolved_issues.is_empty() {
                                 println!("  {} Resolved issues:", "•".green());
                                 for issue in &drift_event.resolved_issues {
-                                    println!("    - [{}] {}", 
+                                    println!("    - [{}] {}: {}", 
                                         format!("{:?}", issue.severity).green(),
+                                        issue.rule_id,
                                         issue.message);
                                 }
                             }
@@ -510,4 +560,41 @@ pub fn display_metrics(application: Option<String>, format: &str) {
     println!("Would display metrics for: {} in {} format", 
         application.unwrap_or("all applications".to_string()),
         format);
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn group_issues(port: u16) -> Vec<Issue> {
+        let config = json!({
+            "tags": { "Name": "web" },
+            "ingress": [{ "protocol": "tcp", "from_port": port, "to_port": port, "cidr_blocks": ["0.0.0.0/0"] }]
+        });
+        FastValidator::new().check_security_group(&ResourceContext::new("sg-web", "aws_security_group", &config))
+    }
+
+    #[test]
+    fn test_drift_is_keyed_on_fingerprints() {
+        let ssh = group_issues(22);
+        let rdp = group_issues(3389);
+        assert!(ssh.iter().any(|issue| rdp.iter().any(|other| other.rule_id == issue.rule_id)));
+
+        // Another port under the same rule is drift in both directions
+        assert!(!unmatched_issues(&rdp, &ssh).is_empty());
+        assert!(!unmatched_issues(&ssh, &rdp).is_empty());
+
+        // Rewording a message is not
+        let reworded: Vec<Issue> = ssh.iter().cloned()
+            .map(|mut issue| {
+                issue.message = format!("{} (reworded)", issue.message);
+                issue
+            })
+            .collect();
+        assert!(unmatched_issues(&reworded, &ssh).is_empty());
+
+        // A second identical finding is still new
+        let doubled: Vec<Issue> = ssh.iter().chain(&ssh[..1]).cloned().collect();
+        assert_eq!(unmatched_issues(&doubled, &ssh).len(), 1);
+    }
+}
@@ -0,0 +1,522 @@
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+use crate::cidr::Cidr;
+use crate::cloudformation::{is_template, template_resources};
+use crate::hcl::SourceResource;
+use crate::protocol::{Protocol, Traffic};
+use crate::types::{is_state, module_path, state_resources, Issue, Plan, Resource, ResourceAddress, ResourceMode, Severity};
+
+/// A set of user-defined security rules loaded from one or more policy files
+#[derive(Debug, Default, Deserialize)]
+pub struct PolicySet {
+    #[serde(default)]
+    pub rules: Vec<PolicyRule>,
+}
+
+/// A single policy rule, e.g. `NoPublicIngress` from the README
+#[derive(Debug, Clone, Deserialize)]
+pub struct PolicyRule {
+    pub name: String,
+    // Stable rule id reported on findings; defaults to the rule name
+    pub id: Option<String>,
+    pub title: Option<String>,
+    pub remediation: Option<String>,
+    pub doc_url: Option<String>,
+    #[serde(default)]
+    pub compliance: Vec<String>,
+    // Resource kind this rule applies to (`security_group`, `aws_s3_bucket`, `*`, ...)
+    #[serde(rename = "type")]
+    pub resource_type: String,
+    #[serde(rename = "match", default)]
+    pub conditions: MatchConditions,
+    #[serde(default)]
+    pub action: PolicyAction,
+    // Falls back to a severity derived from the action when omitted
+    pub severity: Option<Severity>,
+}
+
+/// What happens when a rule matches a resource
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyAction {
+    #[default]
+    Deny,
+    Warn,
+}
+
+/// Conditions a resource must satisfy for a rule to fire. Every condition
+/// that is present has to match; a rule without conditions matches every
+/// resource of its type.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MatchConditions {
+    // Ingress CIDR that triggers the rule, e.g. "0.0.0.0/0"
+    pub cidr: Option<String>,
+    // Ports (or "from-to" ranges) that trigger the rule when reachable by ingress,
+    // optionally limited to one protocol ("udp/53"); bare ports match TCP and UDP
+    #[serde(default)]
+    pub ports: Vec<PortSpec>,
+    // Whether the bucket is publicly readable through its ACL
+    pub public_access: Option<bool>,
+    // Arbitrary attribute paths ("metadata_options.http_tokens") and their expected values
+    #[serde(default)]
+    pub attributes: BTreeMap<String, Value>,
+}
+
+/// A port in a policy file, written either as a number or a string ("22", "8000-8100", "udp/53")
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum PortSpec {
+    Number(i64),
+    Text(String),
+}
+
+impl PortSpec {
+    /// Parse the spec into an optional protocol and an inclusive port range
+    fn range(&self) -> Result<(Option<Protocol>, i64, i64), String> {
+        match self {
+            PortSpec::Number(port) => Ok((None, *port, *port)),
+            PortSpec::Text(text) => {
+                let (protocol, ports) = match text.split_once('/') {
+                    Some((protocol, ports)) => (Some(Protocol::parse(protocol)?), ports),
+                    None => (None, text.as_str()),
+                };
+                let parse = |s: &str| s.trim().parse::<i64>()
+                    .map_err(|_| format!("invalid port '{}'", text));
+                match ports.split_once('-') {
+                    Some((from, to)) => Ok((protocol, parse(from)?, parse(to)?)),
+                    None => parse(ports).map(|p| (protocol, p, p)),
+                }
+            }
+        }
+    }
+}
+
+impl PolicyRule {
+    /// Severity reported for violations of this rule
+    pub fn effective_severity(&self) -> Severity {
+        self.severity.clone().unwrap_or(match self.action {
+            PolicyAction::Deny => Severity::High,
+            PolicyAction::Warn => Severity::Medium,
+        })
+    }
+
+    /// Check whether this rule targets the given Terraform resource type
+    fn applies_to(&self, resource_type: &str) -> bool {
+        // Short names follow the resource kinds used by the drift monitor, and
+        // also cover the CloudFormation type mapped to the same values
+        let (terraform, template) = match self.resource_type.as_str() {
+            "security_group" => ("aws_security_group", "AWS::EC2::SecurityGroup"),
+            "s3_bucket" => ("aws_s3_bucket", "AWS::S3::Bucket"),
+            "ec2_instance" => ("aws_instance", "AWS::EC2::Instance"),
+            other => (other, other),
+        };
+        terraform == "*" || terraform == resource_type || template == resource_type
+    }
+
+    /// Evaluate the rule against a resource configuration, returning a
+    /// description of the violation when every condition matches
+    fn evaluate(&self, resource: &Value) -> Option<String> {
+        let conditions = &self.conditions;
+        let mut details = Vec::new();
+
+        if conditions.cidr.is_some() || !conditions.ports.is_empty() {
+            details.push(self.match_ingress(resource)?);
+        }
+
+        if let Some(expected) = conditions.public_access {
+            let acl = resource.get("acl").and_then(|a| a.as_str()).unwrap_or("private");
+            let is_public = acl == "public-read" || acl == "public-read-write";
+            if is_public != expected {
+                return None;
+            }
+            details.push(format!("public access is {} (ACL: {})", is_public, acl));
+        }
+
+        for (path, expected) in &conditions.attributes {
+            let actual = path.split('.').try_fold(resource, attribute)?;
+            if actual != expected {
+                return None;
+            }
+            details.push(format!("{} = {}", path, actual));
+        }
+
+        if details.is_empty() {
+            details.push("resource matches policy".to_string());
+        }
+        Some(details.join(", "))
+    }
+
+    /// Find an ingress rule matching the configured CIDR and ports
+    fn match_ingress(&self, resource: &Value) -> Option<String> {
+        let conditions = &self.conditions;
+        let ingress = resource.get("ingress").and_then(|i| i.as_array())?;
+
+        for rule in ingress {
+            // Match the source CIDR across IPv4 and IPv6 blocks
+            let source = match &conditions.cidr {
+                Some(cidr) => {
+                    // Compare parsed networks so spelling differences don't matter
+                    let wanted = Cidr::parse(cidr).ok();
+                    let found = ["cidr_blocks", "ipv6_cidr_blocks"].iter()
+                        .filter_map(|key| rule.get(*key).and_then(|b| b.as_array()))
+                        .flatten()
+                        .filter_map(|block| block.as_str())
+                        .any(|block| block == cidr || (wanted.is_some() && Cidr::parse(block).ok() == wanted));
+                    if !found {
+                        continue;
+                    }
+                    cidr.clone()
+                }
+                None => "any source".to_string(),
+            };
+
+            if conditions.ports.is_empty() {
+                return Some(format!("ingress from {}", source));
+            }
+
+            // All-traffic rules open every port; ICMP and other protocols have none
+            let traffic = Traffic::from_rule(rule);
+            let exposed: Vec<String> = conditions.ports.iter()
+                .filter_map(|spec| spec.range().ok())
+                .filter(|(protocol, low, high)| match traffic {
+                    Some(Traffic::All) => true,
+                    Some(Traffic::Ports { protocol: rule_protocol, from, to }) =>
+                        protocol.is_none_or(|p| p == rule_protocol) && *low <= to as i64 && *high >= from as i64,
+                    _ => false,
+                })
+                .map(|(protocol, low, high)| {
+                    let ports = if low == high { low.to_string() } else { format!("{}-{}", low, high) };
+                    match protocol {
+                        Some(protocol) => format!("{}/{}", protocol, ports),
+                        None => ports,
+                    }
+                })
+                .collect();
+
+            if !exposed.is_empty() {
+                return Some(format!("ingress from {} allows port {}", source, exposed.join(", ")));
+            }
+        }
+
+        None
+    }
+}
+
+impl PolicySet {
+    /// Load a policy file, choosing JSON or YAML based on its extension
+    pub fn load(path: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("could not read policy file {}: {}", path, e))?;
+
+        let is_json = Path::new(path).extension().is_some_and(|ext| ext == "json");
+        let set: PolicySet = if is_json {
+            serde_json::from_str(&content).map_err(|e| format!("invalid JSON in policy file {}: {}", path, e))?
+        } else {
+            serde_yaml::from_str(&content).map_err(|e| format!("invalid YAML in policy file {}: {}", path, e))?
+        };
+
+        // Reject malformed port specs up front rather than silently ignoring them
+        for rule in &set.rules {
+            for spec in &rule.conditions.ports {
+                spec.range().map_err(|e| format!("rule {} in {}: {}", rule.name, path, e))?;
+            }
+        }
+
+        Ok(set)
+    }
+
+    /// Load and combine several policy files into one set
+    pub fn load_all(paths: &[String]) -> Result<Self, String> {
+        let mut combined = PolicySet::default();
+        for path in paths {
+            combined.rules.extend(Self::load(path)?.rules);
+        }
+        Ok(combined)
+    }
+
+    /// Evaluate every rule against a Terraform plan or state, or a
+    /// CloudFormation template, reading its resources as `FastValidator::validate` does
+    pub fn evaluate(&self, document: &Value) -> Vec<Issue> {
+        if self.rules.is_empty() {
+            return Vec::new();
+        }
+
+        if is_state(document) {
+            state_resources(document).map(|resources| self.evaluate_resources(&resources)).unwrap_or_default()
+        } else if ["resource_changes", "planned_values", "format_version"].iter().any(|key| document.get(key).is_some()) {
+            Plan::deserialize(document).map(|plan| self.evaluate_plan(&plan)).unwrap_or_default()
+        } else if is_template(document) {
+            template_resources(document).iter()
+                .flat_map(|resource| self.evaluate_resource(&resource.logical_id, &resource.resource_type, &resource.values))
+                .collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Evaluate every rule against the `change.after` state of each planned
+    /// resource, and the values of child module resources without a change
+    fn evaluate_plan(&self, plan: &Plan) -> Vec<Issue> {
+        let mut issues = Vec::new();
+        let mut evaluated = HashSet::new();
+
+        for rc in &plan.resource_changes {
+            if rc.address.is_empty() || rc.mode == ResourceMode::Data {
+                continue;
+            }
+            let address = rc.instance_address();
+            let after = match &rc.change.after {
+                Some(after) if !after.is_null() => after,
+                _ => continue,
+            };
+            if !evaluated.insert(address.clone()) {
+                continue;
+            }
+
+            let check = |values: &Value| self.evaluate_resource(&address, &rc.resource_type, values);
+            issues.extend(rc.change.track_findings(check(after), check));
+        }
+
+        if let Some(planned) = &plan.planned_values {
+            let unchanged = planned.root_module.all_resources().into_iter()
+                .filter(|resource| !evaluated.contains(&resource.instance_address()));
+            issues.extend(self.evaluate_resources(unchanged));
+        }
+
+        issues
+    }
+
+    /// Evaluate every rule against resource instances from a state or values snapshot
+    pub fn evaluate_resources<'r>(&self, resources: impl IntoIterator<Item = &'r Resource>) -> Vec<Issue> {
+        resources.into_iter()
+            .filter(|resource| !resource.address.is_empty() && resource.mode == ResourceMode::Managed)
+            .flat_map(|resource| self.evaluate_resource(&resource.instance_address(), &resource.resource_type, &resource.values))
+            .collect()
+    }
+
+    /// Evaluate every rule against the literal values of `resource` blocks
+    /// parsed from `.tf` files. Findings carry the location of their own block,
+    /// as the same address may be declared in several module directories.
+    pub fn evaluate_source(&self, resources: &[SourceResource]) -> Vec<Issue> {
+        resources.iter()
+            .flat_map(|resource| {
+                self.evaluate_resource(&resource.address, &resource.resource_type, &resource.values).into_iter()
+                    .map(|issue| issue.with_location(Some(resource.location.clone())))
+            })
+            .collect()
+    }
+
+    /// Evaluate the rules that apply to one resource's values
+    fn evaluate_resource(&self, address: &str, resource_type: &str, values: &Value) -> Vec<Issue> {
+        let mut issues = Vec::new();
+        for rule in self.rules.iter().filter(|r| r.applies_to(resource_type)) {
+            if let Some(details) = rule.evaluate(values) {
+                let verdict = match rule.action {
+                    PolicyAction::Deny => "denied",
+                    PolicyAction::Warn => "warning",
+                };
+                issues.push(Issue {
+                    rule_id: rule.id.clone().unwrap_or_else(|| rule.name.clone()),
+                    resource: address.to_string(),
+                    module: module_path(address),
+                    index_key: ResourceAddress::parse(address).and_then(|a| a.index_key()),
+                    change_status: None,
+                    location: None,
+                    source: None,
+                    message: format!("Policy {} {}: {}", rule.name, verdict, details),
+                    severity: rule.effective_severity(),
+                    title: rule.title.clone().unwrap_or_else(|| rule.name.clone()),
+                    remediation: rule.remediation.clone().unwrap_or_default(),
+                    doc_url: rule.doc_url.clone(),
+                    compliance: rule.compliance.clone(),
+                    attributes: BTreeMap::from([("details".to_string(), details)]),
+                });
+            }
+        }
+        issues
+    }
+}
+
+/// Look up `key` below `value`, stepping into the single-element list plans
+/// render nested blocks as, e.g. `"metadata_options": [{"http_tokens": ...}]`
+fn attribute<'v>(value: &'v Value, key: &str) -> Option<&'v Value> {
+    match value {
+        Value::Array(items) if items.len() == 1 => items[0].get(key),
+        other => other.get(key),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn plan_with(resource_type: &str, after: Value) -> Value {
+        json!({
+            "resource_changes": [{
+                "address": format!("{}.test", resource_type),
+                "type": resource_type,
+                "change": { "after": after }
+            }]
+        })
+    }
+
+    #[test]
+    fn test_readme_policy_matches_public_ingress() {
+        let set: PolicySet = serde_yaml::from_str(r#"
+rules:
+  - name: "NoPublicIngress"
+    type: "security_group"
+    match:
+      cidr: "0.0.0.0/0"
+      ports: ["22", "27017", "3306", "5432"]
+    action: "deny"
+"#).unwrap();
+
+        let plan = plan_with("aws_security_group", json!({
+            "ingress": [{ "from_port": 27017, "to_port": 27017, "cidr_blocks": ["0.0.0.0/0"] }]
+        }));
+        let issues = set.evaluate(&plan);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::High);
+        assert!(issues[0].message.contains("27017"));
+
+        let plan = plan_with("aws_security_group", json!({
+            "ingress": [{ "from_port": 443, "to_port": 443, "cidr_blocks": ["0.0.0.0/0"] }]
+        }));
+        assert!(set.evaluate(&plan).is_empty());
+
+        // protocol = "-1" ignores its 0-0 port range and opens everything
+        let plan = plan_with("aws_security_group", json!({
+            "ingress": [{ "protocol": "-1", "from_port": 0, "to_port": 0, "cidr_blocks": ["0.0.0.0/0"] }]
+        }));
+        assert_eq!(set.evaluate(&plan).len(), 1);
+    }
+
+    #[test]
+    fn test_warn_rule_with_attributes() {
+        let set: PolicySet = serde_json::from_value(json!({
+            "rules": [{
+                "name": "RequireImdsV2",
+                "type": "aws_instance",
+                "match": { "attributes": { "metadata_options.http_tokens": "optional" } },
+                "action": "warn"
+            }]
+        })).unwrap();
+
+        // Plans render nested blocks as single-element lists
+        let plan = plan_with("aws_instance", json!({ "metadata_options": [{ "http_tokens": "optional" }] }));
+        let issues = set.evaluate(&plan);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Medium);
+        assert!(issues[0].message.contains("warning"));
+
+        let plan = plan_with("aws_instance", json!({ "metadata_options": [{ "http_tokens": "required" }] }));
+        assert!(set.evaluate(&plan).is_empty());
+    }
+
+    fn public_ingress() -> PolicySet {
+        serde_yaml::from_str(r#"
+rules:
+  - name: "NoPublicSsh"
+    type: "security_group"
+    match:
+      cidr: "0.0.0.0/0"
+      ports: ["22"]
+    action: "deny"
+"#).unwrap()
+    }
+
+    #[test]
+    fn test_state_is_detected() {
+        let state = json!({
+            "version": 4,
+            "resources": [{
+                "mode": "managed",
+                "type": "aws_security_group",
+                "name": "bastion",
+                "instances": [{ "attributes": {
+                    "ingress": [{ "protocol": "tcp", "from_port": 22, "to_port": 22, "cidr_blocks": ["0.0.0.0/0"] }]
+                } }]
+            }, {
+                "mode": "data",
+                "type": "aws_security_group",
+                "name": "shared",
+                "instances": [{ "attributes": {
+                    "ingress": [{ "protocol": "tcp", "from_port": 22, "to_port": 22, "cidr_blocks": ["0.0.0.0/0"] }]
+                } }]
+            }]
+        });
+
+        let issues = public_ingress().evaluate(&state);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].resource, "aws_security_group.bastion");
+    }
+
+    #[test]
+    fn test_template_resources() {
+        let template = json!({
+            "Resources": {
+                "Bastion": {
+                    "Type": "AWS::EC2::SecurityGroup",
+                    "Properties": {
+                        "GroupDescription": "bastion",
+                        "SecurityGroupIngress": [{ "IpProtocol": "tcp", "FromPort": 22, "ToPort": 22, "CidrIp": "0.0.0.0/0" }]
+                    }
+                }
+            }
+        });
+
+        let issues = public_ingress().evaluate(&template);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].resource, "Bastion");
+    }
+
+    #[test]
+    fn test_planned_values_child_modules() {
+        let plan = json!({
+            "format_version": "1.2",
+            "resource_changes": [],
+            "planned_values": { "root_module": { "child_modules": [{
+                "address": "module.net",
+                "resources": [{
+                    "address": "module.net.aws_security_group.bastion",
+                    "mode": "managed",
+                    "type": "aws_security_group",
+                    "name": "bastion",
+                    "values": {
+                        "ingress": [{ "protocol": "tcp", "from_port": 22, "to_port": 22, "cidr_blocks": ["0.0.0.0/0"] }]
+                    }
+                }]
+            }] } }
+        });
+
+        let issues = public_ingress().evaluate(&plan);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].resource, "module.net.aws_security_group.bastion");
+    }
+
+    #[test]
+    fn test_source_findings_carry_their_block_location() {
+        let group = r#"
+resource "aws_security_group" "bastion" {
+  ingress {
+    protocol    = "tcp"
+    from_port   = 22
+    to_port     = 22
+    cidr_blocks = ["0.0.0.0/0"]
+  }
+}
+"#;
+        let mut sources = crate::hcl::parse_source(group, "modules/a/main.tf").unwrap();
+        sources.extend(crate::hcl::parse_source(group, "modules/b/main.tf").unwrap());
+
+        let files: Vec<String> = public_ingress().evaluate_source(&sources).into_iter()
+            .map(|issue| issue.location.unwrap().file)
+            .collect();
+        assert_eq!(files, ["modules/a/main.tf", "modules/b/main.tf"]);
+    }
+}
@@ -0,0 +1,266 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+use crate::protocol::{Protocol, ProtocolPort};
+
+/// Ports the built-in security group checks know about: which are explicitly
+/// allowed, which are disallowed, and the service names used in messages
+#[derive(Debug, Clone)]
+pub struct PortCatalog {
+    pub services: HashMap<ProtocolPort, String>,
+    pub allowed: BTreeSet<ProtocolPort>,
+    pub disallowed: BTreeSet<ProtocolPort>,
+}
+
+impl Default for PortCatalog {
+    /// The built-in catalog
+    fn default() -> Self {
+        // Map ports to their common services for better messages
+        let services = [
+            (ProtocolPort::tcp(22), "SSH"),
+            (ProtocolPort::tcp(3389), "RDP"),
+            (ProtocolPort::tcp(23), "Telnet"),
+            (ProtocolPort::tcp(21), "FTP"),
+            (ProtocolPort::tcp(25), "SMTP"),
+            (ProtocolPort::tcp(53), "DNS"),
+            (ProtocolPort::udp(53), "DNS"),
+            (ProtocolPort::udp(123), "NTP"),
+            (ProtocolPort::udp(161), "SNMP"),
+            (ProtocolPort::tcp(80), "HTTP"),
+            (ProtocolPort::tcp(443), "HTTPS"),
+            (ProtocolPort::udp(443), "HTTP/3"),
+            (ProtocolPort::tcp(3306), "MySQL"),
+            (ProtocolPort::tcp(5432), "PostgreSQL"),
+            (ProtocolPort::tcp(27017), "MongoDB"),
+            (ProtocolPort::tcp(6379), "Redis"),
+            (ProtocolPort::tcp(1433), "MSSQL"),
+            (ProtocolPort::tcp(8080), "HTTP-Alt"),
+            (ProtocolPort::tcp(8443), "HTTPS-Alt"),
+        ].into_iter().map(|(port, name)| (port, name.to_string())).collect();
+
+        // Sensitive ports that should be handled with care
+        let disallowed = [
+            ProtocolPort::tcp(22),    // SSH
+            ProtocolPort::tcp(3389),  // RDP
+            ProtocolPort::tcp(23),    // Telnet
+            ProtocolPort::tcp(21),    // FTP
+            ProtocolPort::tcp(25),    // SMTP
+            ProtocolPort::tcp(53),    // DNS zone transfers
+            ProtocolPort::udp(53),    // DNS, abused for amplification
+            ProtocolPort::udp(161),   // SNMP
+        ].into_iter().collect();
+
+        // Explicitly allowed ports
+        let allowed = [
+            ProtocolPort::tcp(80),    // HTTP
+            ProtocolPort::tcp(443),   // HTTPS
+            ProtocolPort::udp(443),   // HTTP/3
+            ProtocolPort::tcp(8080),  // HTTP-Alt
+            ProtocolPort::tcp(8443),  // HTTPS-Alt
+        ].into_iter().collect();
+
+        Self { services, allowed, disallowed }
+    }
+}
+
+impl PortCatalog {
+    /// A catalog without any ports, for callers that want to start from scratch
+    pub fn empty() -> Self {
+        Self {
+            services: HashMap::new(),
+            allowed: BTreeSet::new(),
+            disallowed: BTreeSet::new(),
+        }
+    }
+
+    /// Explicitly allow a port, taking it off the disallowed list
+    pub fn allow(&mut self, port: ProtocolPort) {
+        self.disallowed.remove(&port);
+        self.allowed.insert(port);
+    }
+
+    /// Disallow a port, taking it off the allowed list
+    pub fn disallow(&mut self, port: ProtocolPort) {
+        self.allowed.remove(&port);
+        self.disallowed.insert(port);
+    }
+
+    /// Name the service listening on a port
+    pub fn name_service(&mut self, port: ProtocolPort, name: &str) {
+        self.services.insert(port, name.to_string());
+    }
+}
+
+/// A port in a catalog file: a number, or a string such as "9443" or "udp/53".
+/// Bare ports apply to both TCP and UDP.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum PortEntry {
+    Number(i64),
+    Text(String),
+}
+
+impl PortEntry {
+    /// The protocol/port pairs this entry stands for
+    fn ports(&self) -> Result<Vec<ProtocolPort>, String> {
+        let text = match self {
+            PortEntry::Number(port) => port.to_string(),
+            PortEntry::Text(text) => text.clone(),
+        };
+        parse_port_key(&text)
+    }
+}
+
+/// Additions to a port catalog. Allowing a port removes it from the
+/// disallowed list and vice versa, so overrides can loosen or tighten.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct PortOverrides {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed: Vec<PortEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disallowed: Vec<PortEntry>,
+    // Service names keyed by port ("9443" or "tcp/9443")
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub services: BTreeMap<String, String>,
+}
+
+impl PortOverrides {
+    fn apply(&self, catalog: &mut PortCatalog) -> Result<(), String> {
+        for entry in &self.allowed {
+            entry.ports()?.into_iter().for_each(|port| catalog.allow(port));
+        }
+        for entry in &self.disallowed {
+            entry.ports()?.into_iter().for_each(|port| catalog.disallow(port));
+        }
+        for (key, name) in &self.services {
+            parse_port_key(key)?.into_iter().for_each(|port| catalog.name_service(port, name));
+        }
+        Ok(())
+    }
+}
+
+/// Port catalog file with optional per-environment and per-application overrides,
+/// applied in that order on top of the base lists
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PortCatalogFile {
+    // Start from the built-in catalog rather than an empty one
+    #[serde(default = "default_inherit")]
+    pub inherit_defaults: bool,
+    #[serde(flatten)]
+    pub base: PortOverrides,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub environments: BTreeMap<String, PortOverrides>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub applications: BTreeMap<String, PortOverrides>,
+}
+
+fn default_inherit() -> bool {
+    true
+}
+
+impl Default for PortCatalogFile {
+    fn default() -> Self {
+        Self {
+            inherit_defaults: true,
+            base: PortOverrides::default(),
+            environments: BTreeMap::new(),
+            applications: BTreeMap::new(),
+        }
+    }
+}
+
+impl PortCatalogFile {
+    /// Load a port catalog file, parsing `.json` files as JSON and anything else as YAML
+    pub fn load(path: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("could not read port catalog {}: {}", path, e))?;
+
+        let is_json = Path::new(path).extension().is_some_and(|ext| ext == "json");
+        let file: PortCatalogFile = if is_json {
+            serde_json::from_str(&content).map_err(|e| format!("invalid JSON in port catalog {}: {}", path, e))?
+        } else {
+            serde_yaml::from_str(&content).map_err(|e| format!("invalid YAML in port catalog {}: {}", path, e))?
+        };
+
+        // Surface malformed ports now rather than on first use
+        file.resolve(None, None).map_err(|e| format!("{} in port catalog {}", e, path))?;
+        for name in file.environments.keys() {
+            file.resolve(Some(name), None).map_err(|e| format!("{} in environment {} of {}", e, name, path))?;
+        }
+        for name in file.applications.keys() {
+            file.resolve(None, Some(name)).map_err(|e| format!("{} in application {} of {}", e, name, path))?;
+        }
+
+        Ok(file)
+    }
+
+    /// Build the catalog for an environment and application. Names without
+    /// overrides in the file fall back to the base lists.
+    pub fn resolve(&self, environment: Option<&str>, application: Option<&str>) -> Result<PortCatalog, String> {
+        let mut catalog = if self.inherit_defaults { PortCatalog::default() } else { PortCatalog::empty() };
+        self.base.apply(&mut catalog)?;
+
+        if let Some(overrides) = environment.and_then(|name| self.environments.get(name)) {
+            overrides.apply(&mut catalog)?;
+        }
+        if let Some(overrides) = application.and_then(|name| self.applications.get(name)) {
+            overrides.apply(&mut catalog)?;
+        }
+
+        Ok(catalog)
+    }
+}
+
+/// Parse "9443", "tcp/9443" or "udp/53" into protocol/port pairs
+fn parse_port_key(text: &str) -> Result<Vec<ProtocolPort>, String> {
+    let (protocols, port) = match text.split_once('/') {
+        Some((protocol, port)) => match Protocol::parse(protocol)? {
+            protocol @ (Protocol::Tcp | Protocol::Udp) => (vec![protocol], port),
+            other => return Err(format!("ports cannot be listed for protocol {} in '{}'", other, text)),
+        },
+        None => (vec![Protocol::Tcp, Protocol::Udp], text),
+    };
+
+    let port = port.trim().parse::<i32>().ok()
+        .filter(|p| (0..=65535).contains(p))
+        .ok_or_else(|| format!("invalid port '{}'", text))?;
+    Ok(protocols.into_iter().map(|protocol| ProtocolPort { protocol, port }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overrides_by_environment_and_application() {
+        let file: PortCatalogFile = serde_yaml::from_str(r#"
+allowed: ["tcp/9443"]
+disallowed: [9200, "tcp/2375"]
+services:
+  tcp/9443: "Platform API"
+  "9200": "Elasticsearch"
+environments:
+  dev:
+    allowed: ["tcp/2375"]
+applications:
+  search:
+    allowed: ["tcp/9200"]
+"#).unwrap();
+
+        let base = file.resolve(Some("prod"), None).unwrap();
+        assert!(base.allowed.contains(&ProtocolPort::tcp(9443)));
+        assert!(base.allowed.contains(&ProtocolPort::tcp(443)));
+        assert!(base.disallowed.contains(&ProtocolPort::udp(9200)));
+        assert!(base.disallowed.contains(&ProtocolPort::tcp(2375)));
+        assert_eq!(base.services[&ProtocolPort::tcp(9200)], "Elasticsearch");
+
+        let dev_search = file.resolve(Some("dev"), Some("search")).unwrap();
+        assert!(dev_search.allowed.contains(&ProtocolPort::tcp(2375)));
+        assert!(!dev_search.disallowed.contains(&ProtocolPort::tcp(9200)));
+        assert!(dev_search.disallowed.contains(&ProtocolPort::udp(9200)));
+
+        assert!(parse_port_key("icmp/8").is_err());
+        assert!(parse_port_key("tcp/70000").is_err());
+    }
+}
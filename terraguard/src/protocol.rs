@@ -0,0 +1,194 @@
+use serde_json::Value;
+use std::fmt;
+
+/// IP protocol of a security group rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Protocol {
+    // "-1" / "all": every protocol, ports are ignored
+    All,
+    Tcp,
+    Udp,
+    Icmp,
+    Icmpv6,
+    // Any other IANA protocol number, e.g. 47 (GRE) or 50 (ESP)
+    Other(u8),
+}
+
+impl Protocol {
+    /// Parse a protocol name or number as accepted by AWS ("tcp", "6", "-1", "all", ...)
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let protocol = match text.trim().to_ascii_lowercase().as_str() {
+            "-1" | "all" => Protocol::All,
+            "tcp" | "6" => Protocol::Tcp,
+            "udp" | "17" => Protocol::Udp,
+            "icmp" | "1" => Protocol::Icmp,
+            "icmpv6" | "58" => Protocol::Icmpv6,
+            other => other.parse::<u8>().map(Protocol::Other)
+                .map_err(|_| format!("'{}' is not a valid IP protocol", text))?,
+        };
+        Ok(protocol)
+    }
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Protocol::All => write!(f, "all"),
+            Protocol::Tcp => write!(f, "tcp"),
+            Protocol::Udp => write!(f, "udp"),
+            Protocol::Icmp => write!(f, "icmp"),
+            Protocol::Icmpv6 => write!(f, "icmpv6"),
+            Protocol::Other(number) => write!(f, "{}", number),
+        }
+    }
+}
+
+/// A single port of a port-based protocol, written as `tcp/22`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ProtocolPort {
+    pub protocol: Protocol,
+    pub port: i32,
+}
+
+impl ProtocolPort {
+    pub const fn tcp(port: i32) -> Self {
+        Self { protocol: Protocol::Tcp, port }
+    }
+
+    pub const fn udp(port: i32) -> Self {
+        Self { protocol: Protocol::Udp, port }
+    }
+}
+
+impl fmt::Display for ProtocolPort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.protocol, self.port)
+    }
+}
+
+/// The traffic a security group rule lets through
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Traffic {
+    // Every protocol and port
+    All,
+    // A TCP or UDP port range
+    Ports { protocol: Protocol, from: i32, to: i32 },
+    // ICMP uses from_port as the type and to_port as the code, -1 meaning any
+    Icmp { v6: bool, icmp_type: Option<i32>, code: Option<i32> },
+    // Protocols without ports, such as GRE or ESP
+    Other(u8),
+}
+
+impl Traffic {
+    /// Read the protocol and port fields of an inline `ingress`/`egress` block.
+    /// Rules without a protocol keep the old reading: no ports or 0-0 means all
+    /// traffic, anything else TCP. Returns `None` for unparseable protocols.
+    pub fn from_rule(rule: &Value) -> Option<Self> {
+        let from = rule.get("from_port").and_then(|p| p.as_i64()).map(|p| p as i32);
+        let to = rule.get("to_port").and_then(|p| p.as_i64()).map(|p| p as i32);
+
+        let protocol = match rule.get("protocol") {
+            Some(Value::String(text)) => Protocol::parse(text).ok()?,
+            Some(Value::Number(number)) => Protocol::parse(&number.to_string()).ok()?,
+            _ if matches!((from, to), (None, None) | (Some(0), Some(0))) => Protocol::All,
+            _ => Protocol::Tcp,
+        };
+
+        let traffic = match protocol {
+            Protocol::All => Traffic::All,
+            Protocol::Tcp | Protocol::Udp => Traffic::Ports {
+                protocol,
+                from: from.unwrap_or(0),
+                to: to.unwrap_or(65535),
+            },
+            Protocol::Icmp | Protocol::Icmpv6 => Traffic::Icmp {
+                v6: protocol == Protocol::Icmpv6,
+                icmp_type: from.filter(|t| *t >= 0),
+                code: to.filter(|c| *c >= 0),
+            },
+            Protocol::Other(number) => Traffic::Other(number),
+        };
+        Some(traffic)
+    }
+
+    pub fn protocol(&self) -> Protocol {
+        match self {
+            Traffic::All => Protocol::All,
+            Traffic::Ports { protocol, .. } => *protocol,
+            Traffic::Icmp { v6: false, .. } => Protocol::Icmp,
+            Traffic::Icmp { v6: true, .. } => Protocol::Icmpv6,
+            Traffic::Other(number) => Protocol::Other(*number),
+        }
+    }
+
+    /// Whether the rule lets `port` through
+    pub fn covers(&self, port: &ProtocolPort) -> bool {
+        match self {
+            Traffic::All => true,
+            Traffic::Ports { protocol, from, to } => *protocol == port.protocol && (*from..=*to).contains(&port.port),
+            _ => false,
+        }
+    }
+
+    /// Ports as recorded in issue attributes: "22-22", "all", or "8/-1" for ICMP type/code
+    pub fn ports_attr(&self) -> String {
+        let any = |value: &Option<i32>| value.map(|v| v.to_string()).unwrap_or_else(|| "-1".to_string());
+        match self {
+            Traffic::Ports { from, to, .. } => format!("{}-{}", from, to),
+            Traffic::Icmp { icmp_type, code, .. } => format!("{}/{}", any(icmp_type), any(code)),
+            Traffic::All | Traffic::Other(_) => "all".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Traffic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Traffic::All => write!(f, "all traffic"),
+            Traffic::Ports { protocol, from, to } if from == to => write!(f, "{}/{}", protocol, from),
+            Traffic::Ports { protocol, from, to } => write!(f, "{}/{}-{}", protocol, from, to),
+            Traffic::Icmp { v6, icmp_type, code } => {
+                let name = if *v6 { "ICMPv6" } else { "ICMP" };
+                match (icmp_type, code) {
+                    (None, _) => write!(f, "all {} types", name),
+                    (Some(t), None) => write!(f, "{} type {}", name, t),
+                    (Some(t), Some(c)) => write!(f, "{} type {} code {}", name, t, c),
+                }
+            }
+            Traffic::Other(number) => write!(f, "protocol {}", number),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_protocol_parse() {
+        assert_eq!(Protocol::parse("-1").unwrap(), Protocol::All);
+        assert_eq!(Protocol::parse("TCP").unwrap(), Protocol::Tcp);
+        assert_eq!(Protocol::parse("17").unwrap(), Protocol::Udp);
+        assert_eq!(Protocol::parse("50").unwrap(), Protocol::Other(50));
+        assert!(Protocol::parse("gre").is_err());
+    }
+
+    #[test]
+    fn test_traffic_from_rule() {
+        let all = Traffic::from_rule(&json!({ "protocol": "-1", "from_port": 0, "to_port": 0 })).unwrap();
+        assert_eq!(all, Traffic::All);
+        assert!(all.covers(&ProtocolPort::tcp(22)));
+
+        let dns = Traffic::from_rule(&json!({ "protocol": "udp", "from_port": 53, "to_port": 53 })).unwrap();
+        assert!(dns.covers(&ProtocolPort::udp(53)));
+        assert!(!dns.covers(&ProtocolPort::tcp(53)));
+
+        let ping = Traffic::from_rule(&json!({ "protocol": "icmp", "from_port": 8, "to_port": -1 })).unwrap();
+        assert_eq!(ping.to_string(), "ICMP type 8");
+        assert!(!ping.covers(&ProtocolPort::tcp(8)));
+
+        // No protocol: 0-0 keeps meaning everything
+        assert_eq!(Traffic::from_rule(&json!({ "from_port": 0, "to_port": 0 })), Some(Traffic::All));
+    }
+}
@@ -1,9 +1,409 @@
-use crate::types::{Issue, Severity};
+use crate::baseline::BaselineEntry;
+use crate::suppression::{InlineSuppression, SuppressedIssue, Suppression};
+use crate::types::{ChangeStatus, Issue, ResourceAddress, Severity};
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
 use colored::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::fs;
+
+/// Output formats supported by `validate`
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Sarif,
+    Junit,
+}
+
+/// Everything a report is rendered from
+pub struct ReportData<'a> {
+    pub input: &'a str,
+    // Issues that are still reported
+    pub issues: &'a [Issue],
+    // Issues hidden by an active suppression
+    pub suppressed: &'a [SuppressedIssue],
+    // `terraguard:ignore` comments in scanned sources, with the issues they hide
+    pub inline_suppressions: &'a [InlineSuppression],
+    // Every checked resource as (input, address), so formats with per-resource
+    // entries can show passing ones
+    pub resources: &'a [(String, String)],
+    // Number of findings hidden by the baseline
+    pub baselined: usize,
+    // Baseline entries that no longer match any finding
+    pub stale_baseline: &'a [BaselineEntry],
+    // Findings on the values before an update that the update removes
+    pub fixed: &'a [Issue],
+}
+
+/// Number of issues found per severity
+#[derive(Debug, Default, Serialize)]
+pub struct Summary {
+    pub total: usize,
+    pub high: usize,
+    pub medium: usize,
+    pub low: usize,
+    pub suppressed: usize,
+    pub baselined: usize,
+    // Findings introduced by the planned changes and findings they fix
+    pub introduced: usize,
+    pub fixed: usize,
+}
+
+impl Summary {
+    pub fn new(data: &ReportData) -> Self {
+        let issues = data.issues;
+        let count = |severity: Severity| issues.iter().filter(|i| i.severity == severity).count();
+        Self {
+            total: issues.len(),
+            high: count(Severity::High),
+            medium: count(Severity::Medium),
+            low: count(Severity::Low),
+            suppressed: data.suppressed.len() + data.inline_suppressions.iter().map(|s| s.suppressed.len()).sum::<usize>(),
+            baselined: data.baselined,
+            introduced: issues.iter().filter(|i| i.change_status == Some(ChangeStatus::Introduced)).count(),
+            fixed: data.fixed.len(),
+        }
+    }
+}
+
+/// Machine-readable report written for `--format json`
+#[derive(Debug, Serialize)]
+pub struct JsonReport<'a> {
+    pub tool: &'static str,
+    pub version: &'static str,
+    pub input: &'a str,
+    pub generated_at: DateTime<Utc>,
+    pub summary: Summary,
+    pub issues: &'a [Issue],
+    pub suppressed: &'a [SuppressedIssue],
+    // Echoed for auditors, including comments that matched nothing
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    pub inline_suppressions: &'a [InlineSuppression],
+    pub stale_baseline: &'a [BaselineEntry],
+    pub fixed: &'a [Issue],
+}
+
+impl<'a> JsonReport<'a> {
+    pub fn new(data: &ReportData<'a>) -> Self {
+        Self {
+            tool: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+            input: data.input,
+            generated_at: Utc::now(),
+            summary: Summary::new(data),
+            issues: data.issues,
+            suppressed: data.suppressed,
+            inline_suppressions: data.inline_suppressions,
+            stale_baseline: data.stale_baseline,
+            fixed: data.fixed,
+        }
+    }
+}
+
+/// Merge findings that are identical across the `count`/`for_each` instances
+/// of one resource into a single finding on `address[*]`, in order of first occurrence
+pub fn collapse_instances(issues: Vec<Issue>) -> Vec<Issue> {
+    type Key = (Option<String>, String, String, Severity, BTreeMap<String, String>);
+    let mut collapsed: Vec<(Issue, Option<String>, usize)> = Vec::new();
+    let mut groups: HashMap<Key, usize> = HashMap::new();
+
+    for issue in issues {
+        let wildcard = ResourceAddress::parse(&issue.resource)
+            .filter(|address| address.index.is_some())
+            .map(|address| address.wildcard());
+        let Some(wildcard) = wildcard else {
+            collapsed.push((issue, None, 1));
+            continue;
+        };
+
+        let key = (issue.source.clone(), issue.rule_id.clone(), wildcard.clone(), issue.severity.clone(), issue.attributes.clone());
+        match groups.get(&key) {
+            Some(&position) => collapsed[position].2 += 1,
+            None => {
+                groups.insert(key, collapsed.len());
+                collapsed.push((issue, Some(wildcard), 1));
+            }
+        }
+    }
+
+    collapsed.into_iter().map(|(mut issue, wildcard, count)| {
+        if let (Some(wildcard), true) = (wildcard, count > 1) {
+            issue.message = format!("{} ({} instances)", issue.message, count);
+            issue.resource = wildcard;
+            issue.index_key = None;
+            issue = issue.with_attribute("instances", count);
+        }
+        issue
+    }).collect()
+}
+
+/// Render the report in the requested format and print it, or write it to
+/// `output` when a file is given
+pub fn write_report(format: OutputFormat, data: &ReportData, output: Option<&str>) -> Result<(), String> {
+    // Never write ANSI escape codes into report files
+    if output.is_some() {
+        colored::control::set_override(false);
+    }
+
+    let rendered = match format {
+        OutputFormat::Text => text_report(data),
+        OutputFormat::Json => serde_json::to_string_pretty(&JsonReport::new(data))
+            .map_err(|e| format!("could not serialize report: {}", e))? + "\n",
+        OutputFormat::Sarif => serde_json::to_string_pretty(&sarif_report(data))
+            .map_err(|e| format!("could not serialize report: {}", e))? + "\n",
+        OutputFormat::Junit => junit_report(data),
+    };
+
+    match output {
+        Some(path) => fs::write(path, rendered)
+            .map_err(|e| format!("could not write report to {}: {}", path, e)),
+        None => {
+            print!("{}", rendered);
+            Ok(())
+        }
+    }
+}
+
+/// Map a severity onto a SARIF result level
+fn sarif_level(severity: &Severity) -> &'static str {
+    match severity {
+        Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Low => "note",
+    }
+}
+
+/// Where a suppressed SARIF result was waived
+enum Waiver<'a> {
+    File(&'a Suppression),
+    Inline(&'a InlineSuppression),
+}
+
+/// Build a SARIF 2.1.0 log with one result per issue. The Terraform resource
+/// address is reported as a logical location, the input file the issue comes
+/// from as the artifact, or the declaring `.tf` file and line when the issue
+/// has a location.
+/// Suppressed issues are included with their justification.
+pub fn sarif_report(data: &ReportData) -> Value {
+    let findings: Vec<(&Issue, Option<Waiver>)> = data.issues.iter()
+        .map(|issue| (issue, None))
+        .chain(data.suppressed.iter().map(|s| (&s.issue, Some(Waiver::File(&s.suppression)))))
+        .chain(data.inline_suppressions.iter()
+            .flat_map(|s| s.suppressed.iter().map(move |issue| (issue, Some(Waiver::Inline(s))))))
+        .collect();
+
+    // Rules are listed once each, in order of first appearance
+    let mut rule_ids: Vec<&str> = Vec::new();
+    for (issue, _) in &findings {
+        if !rule_ids.contains(&issue.rule_id.as_str()) {
+            rule_ids.push(&issue.rule_id);
+        }
+    }
+
+    let rules: Vec<Value> = rule_ids.iter().map(|id| {
+        let (first, _) = findings.iter().find(|(i, _)| i.rule_id == *id).expect("rule id comes from issues");
+        let mut rule = json!({
+            "id": id,
+            "name": first.title,
+            "shortDescription": { "text": first.title },
+            "help": { "text": first.remediation },
+            "defaultConfiguration": { "level": sarif_level(&first.severity) },
+            "properties": { "tags": first.compliance },
+        });
+        if let Some(url) = &first.doc_url {
+            rule["helpUri"] = json!(url);
+        }
+        rule
+    }).collect();
+
+    let results: Vec<Value> = findings.iter().map(|(issue, suppression)| {
+        let mut result = json!({
+            "ruleId": issue.rule_id,
+            "ruleIndex": rule_ids.iter().position(|id| *id == issue.rule_id),
+            "level": sarif_level(&issue.severity),
+            "message": { "text": issue.message },
+            "partialFingerprints": { "terraguard/v1": issue.fingerprint() },
+            "locations": [{
+                "physicalLocation": {
+                    "artifactLocation": { "uri": issue.source.as_deref().unwrap_or(data.input) },
+                },
+                "logicalLocations": [{
+                    "fullyQualifiedName": issue.resource,
+                    "kind": "resource",
+                }],
+            }],
+        });
+        // Findings from `.tf` sources point at the resource block
+        if let Some(location) = &issue.location {
+            result["locations"][0]["physicalLocation"] = json!({
+                "artifactLocation": { "uri": location.file },
+                "region": { "startLine": location.line, "startColumn": location.column },
+            });
+        }
+        // SARIF's baseline comparison maps directly onto the planned change
+        match issue.change_status {
+            Some(ChangeStatus::Introduced) => result["baselineState"] = json!("new"),
+            Some(ChangeStatus::Kept) => result["baselineState"] = json!("unchanged"),
+            _ => {}
+        }
+        match suppression {
+            Some(Waiver::File(suppression)) => result["suppressions"] = json!([{
+                "kind": "external",
+                "status": "accepted",
+                "justification": format!("{} (owner: {}, expires: {})",
+                    suppression.justification, suppression.owner, suppression.expires),
+            }]),
+            Some(Waiver::Inline(suppression)) => result["suppressions"] = json!([{
+                "kind": "inSource",
+                "status": "accepted",
+                "justification": suppression.reason,
+            }]),
+            None => {}
+        }
+        result
+    }).collect();
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    })
+}
+
+/// Escape text for use in XML attributes and element content
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Whether an issue belongs to a resource instance, either directly or
+/// through the `address[*]` it was collapsed into
+fn issue_covers(issue: &Issue, resource: &str) -> bool {
+    issue.resource == resource
+        || (issue.resource.ends_with("[*]")
+            && ResourceAddress::parse(resource).is_some_and(|a| a.index.is_some() && a.wildcard() == issue.resource))
+}
+
+/// Render a JUnit XML report with one testsuite per input, one testcase per
+/// checked resource and one failure per issue. Resources without issues show up
+/// as passing testcases; a collapsed `address[*]` finding fails every instance
+/// it stands for.
+pub fn junit_report(data: &ReportData) -> String {
+    let issue_source = |issue: &Issue| issue.source.clone().unwrap_or_else(|| data.input.to_string());
+
+    // Checked resources first, then any resource only flagged by a policy rule.
+    // Inputs may share addresses, so testcases are keyed by input as well.
+    let mut testcases: Vec<(String, &str)> = data.resources.iter()
+        .map(|(source, address)| (source.clone(), address.as_str()))
+        .collect();
+    for issue in data.issues {
+        let source = issue_source(issue);
+        if !testcases.iter().any(|(s, r)| *s == source && issue_covers(issue, r)) {
+            testcases.push((source, &issue.resource));
+        }
+    }
+    let failures = |source: &str, resource: &str| -> Vec<&Issue> {
+        data.issues.iter().filter(|i| issue_source(i) == source && issue_covers(i, resource)).collect()
+    };
+
+    // Suites in order of first appearance
+    let mut suites: Vec<&str> = Vec::new();
+    for (source, _) in &testcases {
+        if !suites.contains(&source.as_str()) {
+            suites.push(source);
+        }
+    }
+
+    let failing = testcases.iter().filter(|(s, r)| !failures(s, r).is_empty()).count();
+    let timestamp = Utc::now().format("%Y-%m-%dT%H:%M:%S");
+
+    let mut out = String::new();
+    let _ = writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(out, r#"<testsuites name="{}" tests="{}" failures="{}">"#,
+        env!("CARGO_PKG_NAME"), testcases.len(), failing);
+
+    for suite in suites {
+        let cases: Vec<&str> = testcases.iter().filter(|(s, _)| s == suite).map(|(_, r)| *r).collect();
+        let suite_failing = cases.iter().filter(|r| !failures(suite, r).is_empty()).count();
+        let _ = writeln!(out, r#"  <testsuite name="{}" tests="{}" failures="{}" errors="0" skipped="0" timestamp="{}">"#,
+            xml_escape(suite), cases.len(), suite_failing, timestamp);
+
+        for resource in cases {
+            // Group testcases by resource type, e.g. "aws_security_group"
+            let classname = ResourceAddress::parse(resource).map(|a| a.resource_type).unwrap_or_else(|| resource.to_string());
+            let failures = failures(suite, resource);
+
+            if failures.is_empty() {
+                let _ = writeln!(out, r#"    <testcase name="{}" classname="{}"/>"#,
+                    xml_escape(resource), xml_escape(&classname));
+                continue;
+            }
+
+            let _ = writeln!(out, r#"    <testcase name="{}" classname="{}">"#,
+                xml_escape(resource), xml_escape(&classname));
+            for issue in failures {
+                let severity = format!("{:?}", issue.severity).to_uppercase();
+                let _ = writeln!(out, r#"      <failure message="{}" type="{}">[{}] {}: {}</failure>"#,
+                    xml_escape(&issue.message), severity, severity,
+                    xml_escape(&issue.rule_id), xml_escape(&issue.message));
+            }
+            let _ = writeln!(out, "    </testcase>");
+        }
+
+        let _ = writeln!(out, "  </testsuite>");
+    }
+
+    let _ = writeln!(out, "</testsuites>");
+    out
+}
+
+/// Render issues as human-readable text, High severity first. When findings
+/// come from child modules they are grouped by module path instead.
+pub fn text_report(data: &ReportData) -> String {
+    let issues = data.issues;
+    let mut out = String::new();
 
-pub fn print_report(issues: &[Issue]) {
     if issues.is_empty() {
-        println!("{}", "✅ No security issues found.".green().bold());
+        let _ = writeln!(out, "{}", "✅ No security issues found.".green().bold());
+    } else if issues.iter().any(|issue| issue.module.is_some()) {
+        // Root module first, then module paths in order
+        let mut modules: BTreeMap<Option<&str>, Vec<&Issue>> = BTreeMap::new();
+        for issue in issues {
+            modules.entry(issue.module.as_deref()).or_default().push(issue);
+        }
+
+        for (module, mut module_issues) in modules {
+            module_issues.sort_by(|a, b| b.severity.cmp(&a.severity));
+            let heading = format!("📦 {} ({} issue(s)):", module.unwrap_or("Root module"), module_issues.len());
+            let _ = writeln!(out, "{}", heading.cyan().bold());
+            for issue in module_issues {
+                write_issue(&mut out, issue, data.input);
+            }
+        }
     } else {
         let high_severity_issues: Vec<&Issue> = issues
             .iter()
@@ -12,20 +412,9 @@ pub fn print_report(issues: &[Issue]) {
 
         if !high_severity_issues.is_empty() {
             // First, print High severity issues
-            println!("{}", "⚠️  High Severity Issues Found:".red().bold());
+            let _ = writeln!(out, "{}", "⚠️  High Severity Issues Found:".red().bold());
             for issue in high_severity_issues {
-                let severity_text = match issue.severity {
-                    Severity::Low => "LOW".yellow(),
-                    Severity::Medium => "MEDIUM".magenta(),
-                    Severity::High => "HIGH".red().bold(),
-                };
-
-                println!(
-                    "[{}] {}: {}",
-                    severity_text,
-                    issue.resource.bold(),
-                    issue.message
-                );
+                write_issue(&mut out, issue, data.input);
             }
         }
 
@@ -36,21 +425,267 @@ pub fn print_report(issues: &[Issue]) {
             .collect();
 
         if !other_issues.is_empty() {
-            println!("{}", "⚠️  Other Security Issues Found:".yellow().bold());
+            let _ = writeln!(out, "{}", "⚠️  Other Security Issues Found:".yellow().bold());
             for issue in other_issues {
-                let severity_text = match issue.severity {
-                    Severity::Low => "LOW".yellow(),
-                    Severity::Medium => "MEDIUM".magenta(),
-                    Severity::High => "HIGH".red().bold(),
-                };
-
-                println!(
-                    "[{}] {}: {}",
-                    severity_text,
-                    issue.resource.bold(),
-                    issue.message
-                );
+                write_issue(&mut out, issue, data.input);
             }
         }
     }
+
+    if !data.fixed.is_empty() {
+        let _ = writeln!(out, "{}", "🎉 Fixed by this change:".green().bold());
+        for issue in data.fixed {
+            write_issue(&mut out, issue, data.input);
+        }
+    }
+
+    let introduced = issues.iter().filter(|i| i.change_status == Some(ChangeStatus::Introduced)).count();
+    if introduced > 0 {
+        let kept = issues.iter().filter(|i| i.change_status == Some(ChangeStatus::Kept)).count();
+        let _ = writeln!(out, "{}", format!("ℹ️  {} issue(s) introduced by this change, {} pre-existing", introduced, kept).dimmed());
+    }
+
+    if !data.suppressed.is_empty() {
+        let _ = writeln!(out, "{}", format!("ℹ️  {} issue(s) suppressed by exceptions", data.suppressed.len()).dimmed());
+    }
+
+    let inline: usize = data.inline_suppressions.iter().map(|s| s.suppressed.len()).sum();
+    if inline > 0 {
+        let _ = writeln!(out, "{}", format!("ℹ️  {} issue(s) suppressed by terraguard:ignore comments", inline).dimmed());
+    }
+
+    if data.baselined > 0 {
+        let _ = writeln!(out, "{}", format!("ℹ️  {} issue(s) hidden by the baseline", data.baselined).dimmed());
+    }
+
+    if !data.stale_baseline.is_empty() {
+        let _ = writeln!(out, "{}", "🧹 Stale baseline entries (no longer found, can be removed):".cyan().bold());
+        for entry in data.stale_baseline {
+            let _ = writeln!(out, "  {} {} {}: {}", entry.fingerprint.dimmed(), entry.rule_id, entry.resource.bold(), entry.message);
+        }
+    }
+
+    out
+}
+
+/// Write one issue; its source is named when the report covers several inputs
+fn write_issue(out: &mut String, issue: &Issue, input: &str) {
+    let severity_text = match issue.severity {
+        Severity::Low => "LOW".yellow(),
+        Severity::Medium => "MEDIUM".magenta(),
+        Severity::High => "HIGH".red().bold(),
+    };
+
+    let introduced = match issue.change_status {
+        Some(ChangeStatus::Introduced) => format!(" {}", "(introduced by this change)".red()),
+        _ => String::new(),
+    };
+
+    let _ = writeln!(
+        out,
+        "[{}] {} {}: {}{}",
+        severity_text,
+        issue.rule_id.dimmed(),
+        issue.resource.bold(),
+        issue.message,
+        introduced
+    );
+    if let Some(location) = &issue.location {
+        let _ = writeln!(out, "    {}", location.to_string().dimmed());
+    }
+    if let Some(source) = issue.source.as_deref().filter(|source| *source != input) {
+        let _ = writeln!(out, "    {}", format!("in {}", source).dimmed());
+    }
+    if !issue.remediation.is_empty() {
+        let _ = writeln!(out, "    ↳ {}", issue.remediation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::{RuleInfo, EC2_IMDSV2, EC2_PUBLIC_IP, S3_PUBLIC_ACL, SG_OPEN_INGRESS_V4};
+    use crate::suppression::Suppressions;
+
+    fn issue(rule: &RuleInfo, resource: &str, severity: Severity) -> Issue {
+        Issue::new(rule, resource.to_string(), "message".to_string(), severity)
+    }
+
+    fn report_data<'a>(issues: &'a [Issue], resources: &'a [(String, String)]) -> ReportData<'a> {
+        ReportData {
+            input: "plan.json",
+            issues,
+            suppressed: &[],
+            inline_suppressions: &[],
+            resources,
+            baselined: 0,
+            stale_baseline: &[],
+            fixed: &[],
+        }
+    }
+
+    fn checked(source: &str, addresses: &[&str]) -> Vec<(String, String)> {
+        addresses.iter().map(|address| (source.to_string(), address.to_string())).collect()
+    }
+
+    #[test]
+    fn test_json_report() {
+        let issues = [issue(&SG_OPEN_INGRESS_V4, "aws_security_group.web", Severity::High), issue(&S3_PUBLIC_ACL, "aws_s3_bucket.logs", Severity::Low)];
+        let report = serde_json::to_value(JsonReport::new(&report_data(&issues, &[]))).unwrap();
+
+        assert_eq!(report["tool"], "terraguard");
+        assert_eq!(report["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(report["input"], "plan.json");
+        assert!(report["generated_at"].as_str().is_some_and(|t| t.parse::<DateTime<Utc>>().is_ok()));
+        let summary = &report["summary"];
+        assert_eq!(summary["total"], 2);
+        assert_eq!(summary["high"], 1);
+        assert_eq!(summary["medium"], 0);
+        assert_eq!(summary["low"], 1);
+        assert_eq!(summary["suppressed"], 0);
+        assert_eq!(report["issues"][0]["resource"], "aws_security_group.web");
+        assert_eq!(report["issues"][0]["severity"], "High");
+    }
+
+    #[test]
+    fn test_sarif_report() {
+        let issues = [
+            issue(&SG_OPEN_INGRESS_V4, "module.net.aws_security_group.web", Severity::High),
+            issue(&EC2_PUBLIC_IP, "aws_instance.bastion", Severity::Medium),
+            issue(&SG_OPEN_INGRESS_V4, "aws_security_group.db", Severity::High),
+        ];
+        let log = sarif_report(&report_data(&issues, &[]));
+        assert_eq!(log["version"], "2.1.0");
+
+        let run = &log["runs"][0];
+        let rules: Vec<&str> = run["tool"]["driver"]["rules"].as_array().unwrap().iter()
+            .map(|rule| rule["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(rules, ["TG-SG-001", "TG-EC2-001"]);
+        let rule = &run["tool"]["driver"]["rules"][0];
+        assert_eq!(rule["shortDescription"]["text"], SG_OPEN_INGRESS_V4.title);
+        assert_eq!(rule["help"]["text"], SG_OPEN_INGRESS_V4.remediation);
+        assert_eq!(rule["helpUri"], SG_OPEN_INGRESS_V4.doc_url.unwrap());
+        assert_eq!(rule["properties"]["tags"], serde_json::json!(SG_OPEN_INGRESS_V4.compliance));
+
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0]["ruleId"], "TG-SG-001");
+        assert_eq!(results[0]["level"], "error");
+        let location = &results[0]["locations"][0];
+        assert_eq!(location["physicalLocation"]["artifactLocation"]["uri"], "plan.json");
+        assert_eq!(location["logicalLocations"][0]["fullyQualifiedName"], "module.net.aws_security_group.web");
+        assert_eq!(location["logicalLocations"][0]["kind"], "resource");
+
+        assert_eq!(results[1]["ruleIndex"], 1);
+        assert_eq!(results[1]["level"], "warning");
+        assert_eq!(results[2]["ruleIndex"], 0);
+    }
+
+    #[test]
+    fn test_junit_report() {
+        let issues = [Issue {
+            message: "Allows <all> traffic".to_string(),
+            ..issue(&SG_OPEN_INGRESS_V4, "aws_security_group.sg[\"a&b\"]", Severity::High)
+        }];
+        let resources = checked("plans/<prod>.json", &["aws_security_group.sg[\"a&b\"]", "aws_s3_bucket.logs", "aws_instance.w[\"eu.west\"]"]);
+
+        let data = ReportData { input: "plans/<prod>.json", ..report_data(&issues, &resources) };
+        let xml = junit_report(&data);
+        assert!(xml.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+        assert!(xml.contains(r#"<testsuites name="terraguard" tests="3" failures="1">"#));
+        assert!(xml.contains(r#"<testsuite name="plans/&lt;prod&gt;.json" tests="3" failures="1""#));
+        assert!(xml.contains(r#"<testcase name="aws_security_group.sg[&quot;a&amp;b&quot;]" classname="aws_security_group">"#));
+        assert!(xml.contains(r#"<failure message="Allows &lt;all&gt; traffic" type="HIGH">[HIGH] TG-SG-001: Allows &lt;all&gt; traffic</failure>"#));
+        assert!(xml.contains(r#"<testcase name="aws_s3_bucket.logs" classname="aws_s3_bucket"/>"#));
+        // Dots inside an instance key do not split the address
+        assert!(xml.contains(r#"<testcase name="aws_instance.w[&quot;eu.west&quot;]" classname="aws_instance"/>"#));
+        assert!(xml.trim_end().ends_with("</testsuites>"));
+    }
+
+    #[test]
+    fn test_sarif_suppressions() {
+        let suppressions: Suppressions = serde_yaml::from_str(r#"
+suppressions:
+  - rule: TG-EC2-001
+    resource: aws_instance.bastion
+    justification: Jump host
+    owner: platform
+    expires: 2030-01-01
+"#).unwrap();
+        let today = chrono::NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+        let (issues, suppressed) = suppressions.apply(vec![
+            issue(&SG_OPEN_INGRESS_V4, "aws_security_group.web", Severity::High),
+            issue(&EC2_PUBLIC_IP, "aws_instance.bastion", Severity::Medium),
+        ], today);
+        assert_eq!(suppressed[0].status, "suppressed");
+
+        let data = ReportData { suppressed: &suppressed, ..report_data(&issues, &[]) };
+        let results = sarif_report(&data)["runs"][0]["results"].as_array().unwrap().clone();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].get("suppressions").is_none());
+        assert_eq!(results[1]["ruleId"], "TG-EC2-001");
+        assert_eq!(results[1]["suppressions"], serde_json::json!([{
+            "kind": "external",
+            "status": "accepted",
+            "justification": "Jump host (owner: platform, expires: 2030-01-01)",
+        }]));
+
+        // The JSON report keeps the suppressed issue with its status
+        let report = serde_json::to_value(JsonReport::new(&data)).unwrap();
+        assert_eq!(report["summary"]["suppressed"], 1);
+        assert_eq!(report["suppressed"][0]["status"], "suppressed");
+        assert_eq!(report["suppressed"][0]["rule_id"], "TG-EC2-001");
+    }
+
+    #[test]
+    fn test_collapse_instances() {
+        let issue = |rule, resource: &str| Issue::new(rule, resource.to_string(), "message".to_string(), Severity::High);
+        let issues = collapse_instances(vec![
+            issue(&EC2_IMDSV2, "aws_instance.worker[0]"),
+            issue(&EC2_PUBLIC_IP, "aws_instance.worker[0]"),
+            issue(&EC2_IMDSV2, "aws_instance.worker[1]"),
+            issue(&EC2_IMDSV2, "aws_instance.worker[2]"),
+            issue(&EC2_IMDSV2, "aws_instance.web"),
+        ]);
+
+        assert_eq!(issues.len(), 3);
+        assert_eq!(issues[0].resource, "aws_instance.worker[*]");
+        assert_eq!(issues[0].message, "message (3 instances)");
+        assert_eq!(issues[0].index_key, None);
+        // A finding on a single instance keeps its index
+        assert_eq!(issues[1].resource, "aws_instance.worker[0]");
+        assert_eq!(issues[1].index_key, Some(serde_json::json!(0)));
+        assert_eq!(issues[2].resource, "aws_instance.web");
+    }
+
+    #[test]
+    fn test_junit_collapsed_instances_fail() {
+        let issue = |resource: &str| Issue::new(&EC2_IMDSV2, resource.to_string(), "message".to_string(), Severity::High);
+        let issues = collapse_instances(vec![issue("aws_instance.w[0]"), issue("aws_instance.w[1]")]);
+        let resources = checked("plan.json", &["aws_instance.w[0]", "aws_instance.w[1]", "aws_instance.ok"]);
+
+        let xml = junit_report(&report_data(&issues, &resources));
+        assert!(xml.contains(r#"<testsuites name="terraguard" tests="3" failures="2">"#));
+        assert!(xml.contains(r#"<testcase name="aws_instance.w[0]" classname="aws_instance">"#));
+        assert!(xml.contains(r#"<testcase name="aws_instance.w[1]" classname="aws_instance">"#));
+        assert!(xml.contains(r#"<testcase name="aws_instance.ok" classname="aws_instance"/>"#));
+        assert!(!xml.contains("aws_instance.w[*]"));
+    }
+
+    #[test]
+    fn test_junit_suite_per_input() {
+        let issues = [issue(&SG_OPEN_INGRESS_V4, "aws_security_group.web", Severity::High).with_source("prod.json")];
+        let mut resources = checked("dev.json", &["aws_security_group.web"]);
+        resources.extend(checked("prod.json", &["aws_security_group.web"]));
+
+        let data = ReportData { input: "dev.json, prod.json", ..report_data(&issues, &resources) };
+        let xml = junit_report(&data);
+        assert!(xml.contains(r#"<testsuites name="terraguard" tests="2" failures="1">"#));
+        let (dev, prod) = xml.split_once(r#"<testsuite name="prod.json""#).unwrap();
+        assert!(dev.contains(r#"<testsuite name="dev.json" tests="1" failures="0""#));
+        assert!(dev.contains(r#"<testcase name="aws_security_group.web" classname="aws_security_group"/>"#));
+        assert!(prod.starts_with(r#" tests="1" failures="1""#));
+        assert!(prod.contains("[HIGH] TG-SG-001"));
+    }
 }
@@ -1,244 +1,584 @@
 use serde_json::Value;
-use crate::types::{Issue, Severity};
-use std::collections::{HashMap, HashSet};
+use crate::catalog::*;
+use crate::cidr::Cidr;
+use crate::cloudformation::{is_template, template_resources};
+use crate::hcl::SourceResource;
+use crate::ports::PortCatalog;
+use crate::protocol::{ProtocolPort, Traffic};
+use crate::types::{is_state, state_resources, ConfigModule, Issue, Plan, Resource, ResourceAddress, ResourceChange, ResourceMode, Severity};
+use rayon::prelude::*;
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 /// Fast plan validator that focuses on efficiently validating
-/// security configurations in infrastructure plans
+/// security configurations in infrastructure plans. Resources are checked in
+/// parallel; findings and coverage keep the input order.
 pub struct FastValidator {
-    // Cache port information for quick lookups
-    port_services: HashMap<i32, &'static str>,
-    disallowed_ports: HashSet<i32>,
-    allowed_ports: HashSet<i32>,
+    // Cache port information for quick lookups, keyed by protocol and port
+    port_services: HashMap<ProtocolPort, String>,
+    disallowed_ports: BTreeSet<ProtocolPort>,
+    allowed_ports: BTreeSet<ProtocolPort>,
     
     // Cache for already validated resources to avoid duplicate work
-    validated_resources: HashSet<String>
+    validated_resources: HashSet<String>,
+
+    // Resources that at least one check ran against, in plan order
+    checked_resources: Vec<String>,
+
+    // Resource types without a registered checker and how often they were seen
+    unknown_types: BTreeMap<String, usize>,
+
+    // Maps resource types to the checks that apply to them
+    registry: CheckerRegistry,
+
+    // Security group addresses keyed by their known id, and standalone rule
+    // addresses mapped to the group their configuration references
+    security_group_ids: HashMap<String, String>,
+    security_group_refs: HashMap<String, String>,
+
+    // Problems with the input itself, such as an unsupported plan format version
+    warnings: Vec<String>,
+}
+
+/// The kind of input a resource was read from, which decides why some of
+/// its values cannot be determined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueOrigin {
+    // A Terraform plan or state: unknown values are computed during apply
+    Plan,
+    // `.tf` source: unknown values are variables, references, function calls or dynamic blocks
+    Source,
+    // A CloudFormation template: unknown values are parameters, references or intrinsic functions
+    Template,
+}
+
+impl ValueOrigin {
+    /// Why a value of this input cannot be judged, completing "Cannot determine <path>"
+    fn unknown_reason(self) -> &'static str {
+        match self {
+            ValueOrigin::Plan => "until apply; it is computed from other resources",
+            ValueOrigin::Source => "from source; it is set by a variable, reference, function call or dynamic block",
+            ValueOrigin::Template => "from the template; it depends on a parameter, reference or intrinsic function",
+        }
+    }
+}
+
+/// What a checker sees of a single resource
+#[derive(Debug, Clone, Copy)]
+pub struct ResourceContext<'a> {
+    pub address: &'a str,
+    pub resource_type: &'a str,
+    // Attribute values to check: `change.after` for plans, the resource itself otherwise
+    pub values: &'a Value,
+    // The planned change the values come from, when validating a Terraform plan
+    pub change: Option<&'a ResourceChange>,
+    // `after_unknown` of the change: `true` marks values only known after apply
+    pub unknown: Option<&'a Value>,
+    pub origin: ValueOrigin,
+}
+
+impl<'a> ResourceContext<'a> {
+    /// Context for a resource outside of a plan, e.g. a monitored configuration file
+    pub fn new(address: &'a str, resource_type: &'a str, values: &'a Value) -> Self {
+        Self { address, resource_type, values, change: None, unknown: None, origin: ValueOrigin::Plan }
+    }
+
+    /// Paths of the values matching `pattern` that are only known after apply,
+    /// e.g. `ingress.*.cidr_blocks` may give `["ingress.1.cidr_blocks"]`. `*`
+    /// matches any list index; an unknown parent covers everything below it.
+    pub fn unknown_paths(&self, pattern: &str) -> Vec<String> {
+        let segments: Vec<&str> = pattern.split('.').collect();
+        let mut found = Vec::new();
+        if let Some(unknown) = self.unknown {
+            collect_unknown(unknown, &segments, &mut Vec::new(), &mut found);
+        }
+        found
+    }
+
+    /// Whether any value matching `pattern` is only known after apply
+    pub fn is_unknown(&self, pattern: &str) -> bool {
+        !self.unknown_paths(pattern).is_empty()
+    }
+}
+
+/// Walk an `after_unknown` tree along `segments`, recording the paths marked unknown
+fn collect_unknown(tree: &Value, segments: &[&str], walked: &mut Vec<String>, found: &mut Vec<String>) {
+    if tree == &Value::Bool(true) {
+        found.push(walked.join("."));
+        return;
+    }
+    let Some((first, rest)) = segments.split_first() else {
+        return;
+    };
+
+    let mut descend = |child: &Value, key: String, rest: &[&str], found: &mut Vec<String>| {
+        walked.push(key);
+        collect_unknown(child, rest, walked, found);
+        walked.pop();
+    };
+    match tree {
+        // Single blocks may be written as objects in hand-made plans; `*` then matches nothing
+        Value::Object(_) if *first == "*" => collect_unknown(tree, rest, walked, found),
+        Value::Object(map) => {
+            if let Some(child) = map.get(*first) {
+                descend(child, first.to_string(), rest, found);
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                if *first == "*" || *first == index.to_string() {
+                    descend(item, index.to_string(), rest, found);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+// Attributes of a security group rule that decide what it lets through:
+// CIDRs first, then ports and protocol
+const RULE_FIELDS: [&str; 5] = ["cidr_blocks", "ipv6_cidr_blocks", "from_port", "to_port", "protocol"];
+const VPC_RULE_FIELDS: [&str; 5] = ["cidr_ipv4", "cidr_ipv6", "from_port", "to_port", "ip_protocol"];
+
+/// A check run against a single resource configuration
+pub type Checker = fn(&FastValidator, &ResourceContext) -> Vec<Issue>;
+
+/// Maps resource types (Terraform `type`, CloudFormation `Type`) to checkers
+pub struct CheckerRegistry {
+    checkers: HashMap<String, Vec<Checker>>,
+}
+
+impl CheckerRegistry {
+    /// Registry with the built-in AWS checks
+    pub fn with_defaults() -> Self {
+        let mut registry = Self { checkers: HashMap::new() };
+
+        registry.register("aws_security_group", FastValidator::check_security_group);
+        registry.register("aws_instance", FastValidator::check_ec2_instance);
+        registry.register("aws_s3_bucket", FastValidator::check_s3_bucket);
+        registry.register("aws_security_group_rule", FastValidator::check_security_group_rule);
+        registry.register("aws_vpc_security_group_ingress_rule", FastValidator::check_vpc_ingress_rule);
+        registry.register("aws_vpc_security_group_egress_rule", FastValidator::check_vpc_egress_rule);
+
+        registry.register("AWS::EC2::SecurityGroup", FastValidator::check_security_group);
+        registry.register("AWS::EC2::Instance", FastValidator::check_ec2_instance);
+        registry.register("AWS::S3::Bucket", FastValidator::check_s3_bucket);
+        registry.register("AWS::EC2::SecurityGroupIngress", FastValidator::check_security_group_rule);
+        registry.register("AWS::EC2::SecurityGroupEgress", FastValidator::check_security_group_rule);
+
+        registry
+    }
+
+    /// Add a checker for a resource type
+    pub fn register(&mut self, resource_type: &str, checker: Checker) {
+        self.checkers.entry(resource_type.to_string()).or_default().push(checker);
+    }
+
+    /// Checkers registered for a resource type
+    pub fn get(&self, resource_type: &str) -> Option<&[Checker]> {
+        self.checkers.get(resource_type).map(|c| c.as_slice())
+    }
+}
+
+/// Settings for a `FastValidator`
+#[derive(Debug, Clone, Default)]
+pub struct ValidatorConfig {
+    // Allowed and disallowed ports and the service names used in messages
+    pub ports: PortCatalog,
 }
 
 impl FastValidator {
     /// Create a new FastValidator with pre-configured security rules
     pub fn new() -> Self {
-        // Map ports to their common services for better messages
-        let port_services: HashMap<i32, &'static str> = [
-            (22, "SSH"),
-            (3389, "RDP"),
-            (23, "Telnet"),
-            (21, "FTP"),
-            (25, "SMTP"),
-            (53, "DNS"),
-            (80, "HTTP"),
-            (443, "HTTPS"),
-            (3306, "MySQL"),
-            (5432, "PostgreSQL"),
-            (27017, "MongoDB"),
-            (6379, "Redis"),
-            (1433, "MSSQL"),
-            (8080, "HTTP-Alt"),
-            (8443, "HTTPS-Alt"),
-        ].iter().cloned().collect();
-
-        // Sensitive ports that should be handled with care
-        let disallowed_ports: HashSet<i32> = vec![
-            22,    // SSH
-            3389,  // RDP
-            23,    // Telnet
-            21,    // FTP
-            25,    // SMTP
-            53,    // DNS
-        ].into_iter().collect();
-
-        // Explicitly allowed ports
-        let allowed_ports: HashSet<i32> = vec![
-            80,    // HTTP
-            443,   // HTTPS
-            8080,  // HTTP-Alt
-            8443   // HTTPS-Alt
-        ].into_iter().collect();
+        Self::with_config(ValidatorConfig::default())
+    }
+
+    /// Create a FastValidator using a custom port catalog and settings
+    pub fn with_config(config: ValidatorConfig) -> Self {
+        let PortCatalog { services, allowed, disallowed } = config.ports;
 
         Self {
-            port_services,
-            disallowed_ports,
-            allowed_ports,
-            validated_resources: HashSet::new()
+            port_services: services,
+            disallowed_ports: disallowed,
+            allowed_ports: allowed,
+            validated_resources: HashSet::new(),
+            checked_resources: Vec::new(),
+            unknown_types: BTreeMap::new(),
+            registry: CheckerRegistry::with_defaults(),
+            security_group_ids: HashMap::new(),
+            security_group_refs: HashMap::new(),
+            warnings: Vec::new(),
         }
     }
 
-    /// Validate a plan and return a list of security issues
+    /// Addresses of the resources that were checked, in the order they were validated
+    pub fn checked_resources(&self) -> &[String] {
+        &self.checked_resources
+    }
+
+    /// Resource types that no checker is registered for, with how often each was seen
+    pub fn unknown_types(&self) -> &BTreeMap<String, usize> {
+        &self.unknown_types
+    }
+
+    /// Problems with the validated input that did not stop validation
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Validate a Terraform plan or state, or a CloudFormation template, and
+    /// return a list of security issues
     pub fn validate(&mut self, plan: &Value) -> Vec<Issue> {
         let mut issues = Vec::new();
+        // Step 1: Process Terraform state - raw terraform.tfstate or `terraform show -json` of one
+        if is_state(plan) {
+            match state_resources(plan) {
+                Ok(resources) => issues.extend(self.validate_resources(&resources)),
+                Err(e) => self.warnings.push(e),
+            }
+        }
+        // Step 2: Process "resource_changes" and "planned_values" - handles Terraform plan format
+        else if ["resource_changes", "planned_values", "format_version"].iter().any(|key| plan.get(key).is_some()) {
+            match Plan::deserialize(plan) {
+                Ok(plan) => issues.extend(self.validate_plan(&plan)),
+                Err(e) => self.warnings.push(format!("input does not match the Terraform JSON plan format: {}", e)),
+            }
+        }
+        // Step 3: Process "Resources" - handles CloudFormation templates
+        else if is_template(plan) {
+            issues.extend(self.validate_template(plan));
+        }
 
-        // Step 1: Process "resource_changes" - handles Terraform plan format
-        if let Some(resource_changes) = plan.get("resource_changes").and_then(|v| v.as_array()) {
-            for rc in resource_changes {
-                if let Some(address) = rc.get("address").and_then(|a| a.as_str()) {
-                    // Skip already validated resources
-                    if self.validated_resources.contains(address) {
-                        continue;
-                    }
-                    
-                    self.validated_resources.insert(address.to_string());
-                    
-                    if let Some(after) = rc.get("change").and_then(|c| c.get("after")) {
-                        // Process security groups
-                        if address.contains("aws_security_group") {
-                            issues.extend(self.check_security_group(address.to_string(), after));
-                        }
-                        
-                        // Process EC2 instances
-                        if address.contains("aws_instance") {
-                            issues.extend(self.check_ec2_instance(address.to_string(), after));
-                        }
-                        
-                        // Process S3 buckets
-                        if address.contains("aws_s3_bucket") {
-                            issues.extend(self.check_s3_bucket(address.to_string(), after));
-                        }
-                    }
-                }
+        issues
+    }
+
+    /// Validate the resources of a CloudFormation template
+    pub fn validate_template(&mut self, template: &Value) -> Vec<Issue> {
+        let resources = template_resources(template);
+
+        // Standalone rules name their group by logical id
+        for resource in resources.iter().filter(|r| r.resource_type == "AWS::EC2::SecurityGroup") {
+            self.security_group_ids.insert(resource.logical_id.clone(), resource.logical_id.clone());
+        }
+
+        // Skip already validated resources
+        let resources: Vec<_> = resources.into_iter()
+            .filter(|resource| self.validated_resources.insert(resource.logical_id.clone()))
+            .collect();
+
+        self.check_parallel(&resources, |validator, resource| {
+            // Dispatch on the declared type, never on the logical id
+            let context = ResourceContext {
+                address: &resource.logical_id,
+                resource_type: &resource.resource_type,
+                values: &resource.values,
+                change: None,
+                unknown: Some(&resource.unknown),
+                origin: ValueOrigin::Template,
+            };
+            Checked::new(&context, validator.apply_checkers(&context))
+        })
+    }
+
+    /// Validate the literal values of `resource` blocks parsed from `.tf`
+    /// files. Findings carry the location of the block.
+    pub fn validate_source(&mut self, resources: &[SourceResource]) -> Vec<Issue> {
+        // Standalone rules name their group through a reference such as `aws_security_group.web.id`
+        for resource in resources {
+            let parent = resource.references.get("security_group_id")
+                .filter(|r| r.starts_with("aws_security_group."))
+                .map(|r| r.split('.').take(2).collect::<Vec<_>>().join("."));
+            if let Some(parent) = parent {
+                self.security_group_refs.insert(resource.address.clone(), parent);
             }
         }
-        // Step 2: Process "resources" - handles CloudFormation/raw plan format
-        else if let Some(resources) = plan.get("resources").and_then(|v| v.as_object()) {
-            for (address, resource) in resources {
-                // Skip already validated resources
-                if self.validated_resources.contains(address) {
-                    continue;
-                }
-                
-                self.validated_resources.insert(address.to_string());
-                
-                // Process based on resource type
-                if address.contains("SecurityGroup") {
-                    issues.extend(self.check_security_group(address.to_string(), resource));
-                } else if address.contains("Instance") || address.contains("EC2") {
-                    issues.extend(self.check_ec2_instance(address.to_string(), resource));
-                } else if address.contains("S3") || address.contains("Bucket") {
-                    issues.extend(self.check_s3_bucket(address.to_string(), resource));
-                }
+
+        // The same address may be declared in several module directories, so every block is checked
+        self.check_parallel(resources, |validator, resource| {
+            let context = ResourceContext {
+                address: &resource.address,
+                resource_type: &resource.resource_type,
+                values: &resource.values,
+                change: None,
+                unknown: Some(&resource.unknown),
+                origin: ValueOrigin::Source,
+            };
+            let found = validator.apply_checkers(&context).into_iter()
+                .map(|issue| issue.with_location(Some(resource.location.clone())))
+                .collect();
+            Checked::new(&context, found)
+        })
+    }
+
+    /// Validate the planned changes of a typed Terraform plan
+    pub fn validate_plan(&mut self, plan: &Plan) -> Vec<Issue> {
+        if let Some(warning) = plan.format_warning() {
+            self.warnings.push(warning);
+        }
+
+        self.index_security_groups(plan);
+
+        let mut changes = Vec::new();
+        for rc in &plan.resource_changes {
+            // Data sources are read, not managed; deleted objects will not exist after apply
+            if rc.address.is_empty() || rc.mode == ResourceMode::Data || rc.change.is_delete() {
+                continue;
             }
+
+            // Skip already validated resources; every count/for_each instance is its own resource
+            let address = rc.instance_address();
+            if !self.validated_resources.insert(address.clone()) {
+                continue;
+            }
+
+            if let Some(after) = &rc.change.after {
+                changes.push((address, rc, after));
+            }
+        }
+
+        let mut issues = self.check_parallel(&changes, |validator, (address, rc, after)| {
+            let resource_type = match rc.resource_type.as_str() {
+                "" => type_from_address(&rc.address),
+                known => known.to_string(),
+            };
+            let context = ResourceContext {
+                address,
+                resource_type: &resource_type,
+                values: after,
+                change: Some(rc),
+                unknown: rc.change.after_unknown.as_ref(),
+                origin: ValueOrigin::Plan,
+            };
+            let found = validator.apply_checkers(&context);
+            let check = |before: &Value| validator.apply_checkers(&ResourceContext { values: before, unknown: None, ..context });
+            Checked::new(&context, rc.change.track_findings(found, check))
+        });
+
+        // Resources inside (nested) child modules that have no entry in resource_changes
+        if let Some(planned) = &plan.planned_values {
+            issues.extend(self.validate_resources(planned.root_module.all_resources()));
         }
 
         issues
     }
 
+    /// Validate resource instances from a state or values snapshot, which
+    /// carry no planned change
+    pub fn validate_resources<'r>(&mut self, resources: impl IntoIterator<Item = &'r Resource>) -> Vec<Issue> {
+        let resources: Vec<&Resource> = resources.into_iter().collect();
+        for resource in resources.iter().filter(|r| r.resource_type == "aws_security_group") {
+            if let Some(id) = resource.values.get("id").and_then(|i| i.as_str()) {
+                self.security_group_ids.entry(id.to_string()).or_insert_with(|| resource.instance_address());
+            }
+        }
+
+        let mut pending = Vec::new();
+        for resource in resources {
+            if resource.address.is_empty() || resource.mode == ResourceMode::Data {
+                continue;
+            }
+            let address = resource.instance_address();
+            if self.validated_resources.insert(address.clone()) {
+                pending.push((address, resource));
+            }
+        }
+
+        self.check_parallel(&pending, |validator, (address, resource)| {
+            let resource_type = match resource.resource_type.as_str() {
+                "" => type_from_address(&resource.address),
+                known => known.to_string(),
+            };
+            let context = ResourceContext::new(address, &resource_type, &resource.values);
+            Checked::new(&context, validator.apply_checkers(&context))
+        })
+    }
+
+    /// Check every item across the rayon thread pool, then record coverage and
+    /// collect findings in input order, so the output does not depend on scheduling
+    fn check_parallel<T: Sync>(&mut self, items: &[T], check: impl Fn(&Self, &T) -> Checked + Sync) -> Vec<Issue> {
+        let validator = &*self;
+        let results: Vec<Checked> = items.par_iter().map(|item| check(validator, item)).collect();
+
+        let mut issues = Vec::new();
+        for checked in results {
+            self.record_coverage(checked.address, &checked.resource_type);
+            issues.extend(checked.issues);
+        }
+        issues
+    }
+
+    /// Remember which security group each standalone rule belongs to, using
+    /// known group ids first and configuration references second
+    fn index_security_groups(&mut self, plan: &Plan) {
+        for rc in plan.resource_changes.iter().filter(|rc| rc.resource_type == "aws_security_group") {
+            if let Some(id) = rc.change.after.as_ref().and_then(|a| a.get("id")).and_then(|i| i.as_str()) {
+                self.security_group_ids.insert(id.to_string(), rc.instance_address());
+            }
+        }
+
+        if let Some(configuration) = &plan.configuration {
+            self.index_security_group_refs(&configuration.root_module, "");
+        }
+    }
+
+    /// Walk a configuration module (and its module calls) for `security_group_id` references
+    fn index_security_group_refs(&mut self, module: &ConfigModule, prefix: &str) {
+        for resource in &module.resources {
+            // References look like ["aws_security_group.web.id", "aws_security_group.web"]
+            let parent = resource.references("security_group_id").into_iter()
+                .find(|r| r.starts_with("aws_security_group."))
+                .map(|r| r.split('.').take(2).collect::<Vec<_>>().join("."));
+
+            if let Some(parent) = parent {
+                self.security_group_refs.insert(format!("{}{}", prefix, resource.address), format!("{}{}", prefix, parent));
+            }
+        }
+
+        for (name, call) in &module.module_calls {
+            self.index_security_group_refs(&call.module, &format!("{}module.{}.", prefix, name));
+        }
+    }
+
+    /// Resolve the security group a standalone rule belongs to, if the plan knows it
+    fn parent_security_group(&self, address: &str, resource: &Value) -> Option<String> {
+        if let Some(id) = resource.get("security_group_id").and_then(|i| i.as_str()) {
+            if let Some(parent) = self.security_group_ids.get(id) {
+                return Some(parent.clone());
+            }
+        }
+
+        // Configuration addresses carry no instance keys
+        let base = ResourceAddress::parse(address).map(|a| a.config_address())?;
+        self.security_group_refs.get(&base).cloned()
+    }
+
+    /// Record a resource as checked, or its type as one without checkers
+    fn record_coverage(&mut self, address: String, resource_type: &str) {
+        if self.registry.get(resource_type).is_none() {
+            *self.unknown_types.entry(resource_type.to_string()).or_insert(0) += 1;
+            return;
+        }
+        self.checked_resources.push(address);
+    }
+
+    /// Run the registered checkers without recording coverage, which
+    /// `check_parallel` does afterwards in input order
+    fn apply_checkers(&self, context: &ResourceContext) -> Vec<Issue> {
+        self.registry.get(context.resource_type)
+            .map(|checkers| checkers.iter().flat_map(|checker| checker(self, context)).collect())
+            .unwrap_or_default()
+    }
+
     /// Check security group for issues
-   pub  fn check_security_group(&self, address: String, resource: &Value) -> Vec<Issue> {
+   pub  fn check_security_group(&self, context: &ResourceContext) -> Vec<Issue> {
+        let address = context.address.to_string();
         let mut issues = Vec::new();
+
+        // Rules whose protocol or ports are only known after apply cannot be judged yet
+        let mut resource = context.values.clone();
+        for direction in ["ingress", "egress"] {
+            let fields = RULE_FIELDS.map(|field| format!("{}.*.{}", direction, field));
+            issues.extend(self.check_unknown(context, &fields));
+
+            if let Some(rules) = resource.get_mut(direction).and_then(|r| r.as_array_mut()) {
+                let mut index = 0;
+                rules.retain(|_| {
+                    let pending = context.is_unknown(&format!("{}.{}", direction, index))
+                        || RULE_FIELDS[2..].iter().any(|field| context.is_unknown(&format!("{}.{}.{}", direction, index, field)));
+                    index += 1;
+                    !pending
+                });
+            }
+        }
+        let resource = &resource;
         
         // Check for open ingress
         issues.extend(self.check_open_ingress(address.clone(), resource));
         
         // Check for disallowed ports
         issues.extend(self.check_ports(address.clone(), resource));
-        
-        // Check for overly permissive rules
-        if let Some(ingress) = resource.get("ingress").and_then(|i| i.as_array()) {
-            for rule in ingress {
-                // Check for overly permissive CIDR blocks
-                if let Some(blocks) = rule.get("cidr_blocks").and_then(|b| b.as_array()) {
-                    for cidr in blocks {
-                        if let Some(cidr_str) = cidr.as_str() {
-                            if cidr_str == "0.0.0.0/0" {
-                                let port_info = if let (Some(from), Some(to)) = (
-                                    rule.get("from_port").and_then(|p| p.as_i64()), 
-                                    rule.get("to_port").and_then(|p| p.as_i64())
-                                ) {
-                                    let from = from as i32;
-                                    let to = to as i32;
-                                    
-                                    // Check if any of these ports are particularly sensitive
-                                    let mut port_text = format!("ports {}-{}", from, to);
-                                    
-                                    // If it's a single port, try to identify the service
-                                    if from == to && self.port_services.contains_key(&from) {
-                                        port_text = format!("port {} ({})", from, self.port_services[&from]);
-                                    }
-                                    
-                                    port_text
-                                } else {
-                                    "all ports".to_string()
-                                };
-                                
-                                issues.push(Issue {
-                                    resource: address.clone(),
-                                    message: format!("Open ingress from 0.0.0.0/0 for {}", port_info),
-                                    severity: Severity::High,
-                                });
-                            }
-                        }
-                    }
-                }
-            }
-        }
+
+        // Check for CIDRs that cannot be parsed
+        issues.extend(self.check_malformed_cidrs(address.clone(), resource));
         
         // Check for missing security controls
-        if !resource.get("tags").is_some() {
-            issues.push(Issue {
-                resource: address.clone(),
-                message: "Security group is missing required tags".to_string(),
-                severity: Severity::Medium,
-            });
+        issues.extend(self.check_unknown(context, &["tags"]));
+        if resource.get("tags").is_none() && !context.is_unknown("tags") {
+            issues.push(Issue::new(
+                &SG_MISSING_TAGS,
+                address.clone(),
+                "Security group is missing required tags".to_string(),
+                Severity::Medium,
+            ));
         }
         
         issues
     }
     
-    /// Check for open ingress from 0.0.0.0/0
+    /// "Cannot determine" findings for the values matching `patterns` that are
+    /// only known after apply, instead of judging them as missing
+    fn check_unknown(&self, context: &ResourceContext, patterns: &[impl AsRef<str>]) -> Vec<Issue> {
+        let paths: BTreeSet<String> = patterns.iter()
+            .flat_map(|pattern| context.unknown_paths(pattern.as_ref()))
+            .collect();
+
+        paths.into_iter().map(|path| Issue::new(
+            &PLAN_UNKNOWN_VALUE,
+            context.address.to_string(),
+            format!("Cannot determine {} {}", path, context.origin.unknown_reason()),
+            Severity::Low,
+        )
+        .with_attribute("attribute", path)).collect()
+    }
+
+    /// Check for ingress from ranges that expose the resource to the internet
     fn check_open_ingress(&self, address: String, resource: &Value) -> Vec<Issue> {
         let mut issues = Vec::new();
 
         if let Some(ingress) = resource.get("ingress").and_then(|i| i.as_array()) {
             for rule in ingress {
-                // Check CIDR blocks
-                if let Some(blocks) = rule.get("cidr_blocks").and_then(|b| b.as_array()) {
-                    for cidr in blocks {
-                        if let Some(cidr_str) = cidr.as_str() {
-                            if cidr_str == "0.0.0.0/0" {
-                                // Check what port this open ingress applies to
-                                let port_info = if let (Some(from), Some(to)) = (
-                                    rule.get("from_port").and_then(|p| p.as_i64()), 
-                                    rule.get("to_port").and_then(|p| p.as_i64())
-                                ) {
-                                    let from = from as i32;
-                                    let to = to as i32;
-                                    
-                                    // Check if any of these ports are particularly sensitive
-                                    let mut port_text = format!("ports {}-{}", from, to);
-                                    
-                                    // If it's a single port, try to identify the service
-                                    if from == to && self.port_services.contains_key(&from) {
-                                        port_text = format!("port {} ({})", from, self.port_services[&from]);
-                                    }
-                                    
-                                    port_text
-                                } else {
-                                    "all ports".to_string()
-                                };
-                                
-                                issues.push(Issue {
-                                    resource: address.clone(),
-                                    message: format!("Open ingress from 0.0.0.0/0 for {}", port_info),
-                                    severity: Severity::High,
-                                });
-                                
-                                // Only add the issue once per rule to avoid duplicates
-                                break;
-                            }
-                        }
+                let traffic = match Traffic::from_rule(rule) {
+                    Some(traffic) => traffic,
+                    None => continue,
+                };
+
+                // Judge each range by what it covers, not how it is spelled
+                for (cidr_str, cidr) in parsed_cidrs(rule) {
+                    if !cidr.is_internet_exposed() {
+                        continue;
                     }
+
+                    let rule_info = if cidr.is_ipv6() { &SG_OPEN_INGRESS_V6 } else { &SG_OPEN_INGRESS_V4 };
+                    issues.push(Issue::new(
+                        rule_info,
+                        address.clone(),
+                        format!("Open ingress from {} ({}) for {}", cidr_str.trim(), cidr.describe(), self.describe_traffic(&traffic)),
+                        Severity::High,
+                    )
+                    .with_attribute("cidr", cidr)
+                    .with_attribute("protocol", traffic.protocol())
+                    .with_attribute("ports", traffic.ports_attr()));
                 }
-                
-                // Also check IPv6 CIDR blocks
-                if let Some(blocks) = rule.get("ipv6_cidr_blocks").and_then(|b| b.as_array()) {
-                    for cidr in blocks {
-                        if let Some(cidr_str) = cidr.as_str() {
-                            if cidr_str == "::/0" {
-                                issues.push(Issue {
-                                    resource: address.clone(),
-                                    message: "Open ingress from ::/0 (all IPv6 addresses)".to_string(),
-                                    severity: Severity::High,
-                                });
-                                break;
-                            }
-                        }
+            }
+        }
+
+        issues
+    }
+
+    /// Report CIDR blocks in ingress or egress rules that are not valid networks
+    fn check_malformed_cidrs(&self, address: String, resource: &Value) -> Vec<Issue> {
+        let mut issues = Vec::new();
+
+        for direction in ["ingress", "egress"] {
+            for rule in resource.get(direction).and_then(|r| r.as_array()).into_iter().flatten() {
+                for cidr_str in cidr_strings(rule) {
+                    if let Err(e) = Cidr::parse(cidr_str) {
+                        issues.push(Issue::new(
+                            &SG_MALFORMED_CIDR,
+                            address.clone(),
+                            format!("Malformed CIDR \"{}\" in {} rule: {}", cidr_str, direction, e),
+                            Severity::Medium,
+                        )
+                        .with_attribute("cidr", cidr_str));
                     }
                 }
             }
@@ -247,103 +587,141 @@ impl FastValidator {
         issues
     }
 
-    /// Check ports against allowed and disallowed lists
+    /// Describe the traffic a rule lets through, naming well-known services
+    fn describe_traffic(&self, traffic: &Traffic) -> String {
+        match traffic {
+            Traffic::Ports { protocol, from, to } if from == to => {
+                let port = ProtocolPort { protocol: *protocol, port: *from };
+                match self.port_services.get(&port) {
+                    Some(service) => format!("{} ({})", port, service),
+                    None => port.to_string(),
+                }
+            }
+            _ => traffic.to_string(),
+        }
+    }
+
+    /// Disallowed ports a rule lets through, e.g. "tcp/22 (SSH), udp/53 (DNS)"
+    fn disallowed_in(&self, traffic: &Traffic) -> Vec<String> {
+        self.disallowed_ports.iter()
+            .filter(|port| traffic.covers(port))
+            .map(|port| self.describe_traffic(&Traffic::Ports { protocol: port.protocol, from: port.port, to: port.port }))
+            .collect()
+    }
+
+    /// Check ports against allowed and disallowed lists. Lists hold protocol and
+    /// port pairs, so UDP 53 and TCP 53 are judged separately; ICMP and other
+    /// port-less protocols are not compared against them.
    pub  fn check_ports(&self, resource_address: String, resource: &Value) -> Vec<Issue> {
         let mut issues = Vec::new();
 
         // Check ingress rules
-        if let Some(ingress_rules) = resource.get("ingress") {
-            if let Some(rules) = ingress_rules.as_array() {
-                for rule in rules {
-                    if let (Some(from_port), Some(to_port)) = (rule.get("from_port"), rule.get("to_port")) {
-                        if let (Some(f), Some(t)) = (from_port.as_i64(), to_port.as_i64()) {
-                            let f = f as i32;
-                            let t = t as i32;
-
-                            // Check port range
-                            if f != t {
-                                // Check if range contains disallowed ports
-                                let has_disallowed = self.disallowed_ports.iter()
-                                    .any(|&p| p >= f && p <= t);
-                                
-                                if has_disallowed {
-                                    issues.push(Issue {
-                                        resource: resource_address.clone(),
-                                        message: format!("Port range {}-{} contains disallowed ports", f, t),
-                                        severity: Severity::High,
-                                    });
-                                } else if t - f > 1000 {
-                                    // Large port ranges are suspicious
-                                    issues.push(Issue {
-                                        resource: resource_address.clone(),
-                                        message: format!("Overly permissive port range {}-{}", f, t),
-                                        severity: Severity::Medium,
-                                    });
-                                }
-                            } else {
-                                // Single port checks
-                                // Check from_port (same as to_port in this case)
-                                if self.disallowed_ports.contains(&f) {
-                                    let service = self.port_services.get(&f).unwrap_or(&"unknown service");
-                                    issues.push(Issue {
-                                        resource: resource_address.clone(),
-                                        message: format!("Port {} ({}) is disallowed for inbound traffic", f, service),
-                                        severity: Severity::High,
-                                    });
-                                } else if !self.allowed_ports.contains(&f) {
-                                    issues.push(Issue {
-                                        resource: resource_address.clone(),
-                                        message: format!("Port {} is not explicitly allowed for inbound traffic", f),
-                                        severity: Severity::Medium,
-                                    });
-                                }
-                            }
-                        }
+        for rule in resource.get("ingress").and_then(|r| r.as_array()).into_iter().flatten() {
+            let traffic = match Traffic::from_rule(rule) {
+                Some(traffic) => traffic,
+                None => continue,
+            };
+
+            match traffic {
+                Traffic::All => {
+                    // Every port is open; how bad that is depends on who can reach it
+                    let exposed = parsed_cidrs(rule).any(|(_, cidr)| cidr.is_internet_exposed());
+                    let mut message = "Ingress rule allows all protocols and ports".to_string();
+                    let disallowed = self.disallowed_in(&traffic);
+                    if !disallowed.is_empty() {
+                        message = format!("{}, including disallowed {}", message, disallowed.join(", "));
                     }
+                    issues.push(Issue::new(
+                        &SG_ALL_TRAFFIC_INGRESS,
+                        resource_address.clone(),
+                        message,
+                        if exposed { Severity::High } else { Severity::Medium },
+                    )
+                    .with_attribute("protocol", traffic.protocol()));
                 }
+                Traffic::Ports { protocol, from: f, to: t } if f != t => {
+                    // Check if range contains disallowed ports
+                    let disallowed = self.disallowed_in(&traffic);
+
+                    if !disallowed.is_empty() {
+                        issues.push(Issue::new(
+                            &SG_DISALLOWED_PORT,
+                            resource_address.clone(),
+                            format!("Port range {} contains disallowed ports {}", traffic, disallowed.join(", ")),
+                            Severity::High,
+                        )
+                        .with_attribute("protocol", protocol)
+                        .with_attribute("ports", traffic.ports_attr()));
+                    } else if t - f > 1000 {
+                        // Large port ranges are suspicious
+                        issues.push(Issue::new(
+                            &SG_WIDE_INGRESS_RANGE,
+                            resource_address.clone(),
+                            format!("Overly permissive port range {}", traffic),
+                            Severity::Medium,
+                        )
+                        .with_attribute("protocol", protocol)
+                        .with_attribute("ports", traffic.ports_attr()));
+                    }
+                }
+                Traffic::Ports { protocol, from: f, .. } => {
+                    // Single port checks
+                    let port = ProtocolPort { protocol, port: f };
+                    if self.disallowed_ports.contains(&port) {
+                        issues.push(Issue::new(
+                            &SG_DISALLOWED_PORT,
+                            resource_address.clone(),
+                            format!("Port {} is disallowed for inbound traffic", self.describe_traffic(&traffic)),
+                            Severity::High,
+                        )
+                        .with_attribute("protocol", protocol)
+                        .with_attribute("ports", traffic.ports_attr()));
+                    } else if !self.allowed_ports.contains(&port) {
+                        issues.push(Issue::new(
+                            &SG_PORT_NOT_ALLOWED,
+                            resource_address.clone(),
+                            format!("Port {} is not explicitly allowed for inbound traffic", self.describe_traffic(&traffic)),
+                            Severity::Medium,
+                        )
+                        .with_attribute("protocol", protocol)
+                        .with_attribute("ports", traffic.ports_attr()));
+                    }
+                }
+                Traffic::Icmp { .. } | Traffic::Other(_) => {}
             }
         }
 
         // Check egress rules
-        if let Some(egress_rules) = resource.get("egress") {
-            if let Some(rules) = egress_rules.as_array() {
-                for rule in rules {
-                    // Check for overly permissive egress
-                    if let Some(cidr_blocks) = rule.get("cidr_blocks").and_then(|b| b.as_array()) {
-                        for cidr in cidr_blocks {
-                            if cidr == "0.0.0.0/0" {
-                                // Check if this is for all ports
-                                let all_ports = rule.get("from_port").and_then(|p| p.as_i64()).unwrap_or(0) == 0 &&
-                                              rule.get("to_port").and_then(|p| p.as_i64()).unwrap_or(0) == 0;
-                                
-                                if all_ports {
-                                    issues.push(Issue {
-                                        resource: resource_address.clone(),
-                                        message: "Unrestricted egress to 0.0.0.0/0 for all ports".to_string(),
-                                        severity: Severity::Medium,
-                                    });
-                                    break;
-                                }
-                            }
-                        }
-                    }
-                    
-                    // Check specific ports
-                    if let (Some(from_port), Some(to_port)) = (rule.get("from_port"), rule.get("to_port")) {
-                        if let (Some(f), Some(t)) = (from_port.as_i64(), to_port.as_i64()) {
-                            let f = f as i32;
-                            let t = t as i32;
-                            
-                            // Check for overly permissive ranges
-                            if t - f > 1000 {
-                                issues.push(Issue {
-                                    resource: resource_address.clone(),
-                                    message: format!("Overly permissive egress port range {}-{}", f, t),
-                                    severity: Severity::Low,
-                                });
-                            }
-                        }
-                    }
+        for rule in resource.get("egress").and_then(|r| r.as_array()).into_iter().flatten() {
+            let traffic = match Traffic::from_rule(rule) {
+                Some(traffic) => traffic,
+                None => continue,
+            };
+
+            // Check for overly permissive egress
+            if traffic == Traffic::All {
+                if let Some((cidr_str, cidr)) = parsed_cidrs(rule).find(|(_, c)| c.is_internet_exposed()) {
+                    issues.push(Issue::new(
+                        &SG_UNRESTRICTED_EGRESS,
+                        resource_address.clone(),
+                        format!("Unrestricted egress to {} for all traffic", cidr_str.trim()),
+                        Severity::Medium,
+                    )
+                    .with_attribute("cidr", cidr));
+                }
+            }
+
+            // Check for overly permissive ranges
+            if let Traffic::Ports { protocol, from: f, to: t } = traffic {
+                if t - f > 1000 {
+                    issues.push(Issue::new(
+                        &SG_WIDE_EGRESS_RANGE,
+                        resource_address.clone(),
+                        format!("Overly permissive egress port range {}", traffic),
+                        Severity::Low,
+                    )
+                    .with_attribute("protocol", protocol)
+                    .with_attribute("ports", traffic.ports_attr()));
                 }
             }
         }
@@ -351,30 +729,85 @@ impl FastValidator {
         issues
     }
     
+    /// Check a standalone `aws_security_group_rule`
+    pub fn check_security_group_rule(&self, context: &ResourceContext) -> Vec<Issue> {
+        let resource = context.values;
+        let direction = resource.get("type").and_then(|t| t.as_str()).unwrap_or("ingress");
+        let rule = serde_json::json!({
+            "from_port": resource.get("from_port"),
+            "to_port": resource.get("to_port"),
+            "protocol": resource.get("protocol"),
+            "cidr_blocks": resource.get("cidr_blocks"),
+            "ipv6_cidr_blocks": resource.get("ipv6_cidr_blocks"),
+        });
+        self.check_flat_rule(context, direction, rule, &RULE_FIELDS)
+    }
+
+    /// Check an `aws_vpc_security_group_ingress_rule`
+    pub fn check_vpc_ingress_rule(&self, context: &ResourceContext) -> Vec<Issue> {
+        self.check_flat_rule(context, "ingress", vpc_rule_to_inline(context.values), &VPC_RULE_FIELDS)
+    }
+
+    /// Check an `aws_vpc_security_group_egress_rule`
+    pub fn check_vpc_egress_rule(&self, context: &ResourceContext) -> Vec<Issue> {
+        self.check_flat_rule(context, "egress", vpc_rule_to_inline(context.values), &VPC_RULE_FIELDS)
+    }
+
+    /// Run the inline rule checks on a single rule normalized to the inline
+    /// `ingress`/`egress` block shape, and tie findings to the parent group.
+    /// `fields` names the resource's CIDR, port and protocol attributes in
+    /// `RULE_FIELDS` order.
+    fn check_flat_rule(&self, context: &ResourceContext, direction: &str, rule: Value, fields: &[&str; 5]) -> Vec<Issue> {
+        let (address, resource) = (context.address.to_string(), context.values);
+        let mut issues = self.check_unknown(context, fields);
+
+        // The traffic of a rule with unknown ports or protocol cannot be judged yet
+        if !fields[2..].iter().any(|field| context.is_unknown(field)) {
+            let group = serde_json::json!({ direction: [rule] });
+            issues.extend(self.check_open_ingress(address.clone(), &group));
+            issues.extend(self.check_ports(address.clone(), &group));
+            issues.extend(self.check_malformed_cidrs(address.clone(), &group));
+        }
+
+        if let Some(parent) = self.parent_security_group(&address, resource) {
+            issues = issues.into_iter()
+                .map(|mut issue| {
+                    issue.message = format!("{} (security group {})", issue.message, parent);
+                    issue.with_attribute("security_group", &parent)
+                })
+                .collect();
+        }
+
+        issues
+    }
+
     /// Check EC2 instance configuration for security issues
-   pub fn check_ec2_instance(&self, address: String, resource: &Value) -> Vec<Issue> {
-        let mut issues = Vec::new();
+   pub fn check_ec2_instance(&self, context: &ResourceContext) -> Vec<Issue> {
+        let (address, resource) = (context.address.to_string(), context.values);
+        let mut issues = self.check_unknown(context, &["associate_public_ip_address", "metadata_options.*.http_tokens"]);
     
         // Check for public IP assignment
         if let Some(public_ip) = resource.get("associate_public_ip_address") {
             if public_ip.as_bool().unwrap_or(false) {
-                issues.push(Issue {
-                    resource: address.clone(),
-                    message: "Instance has a public IP address assigned".to_string(),
-                    severity: Severity::Medium,
-                });
+                issues.push(Issue::new(
+                    &EC2_PUBLIC_IP,
+                    address.clone(),
+                    "Instance has a public IP address assigned".to_string(),
+                    Severity::Medium,
+                ));
             }
         }
         
         // Check for IMDSv2 enforcement
-        if let Some(metadata_options) = resource.get("metadata_options") {
+        if let Some(metadata_options) = block(resource, "metadata_options") {
             if let Some(http_tokens) = metadata_options.get("http_tokens") {
                 if http_tokens.as_str().unwrap_or("") != "required" {
-                    issues.push(Issue {
-                        resource: address,
-                        message: "IMDSv2 (token-based) is not enforced, vulnerable to SSRF attacks".to_string(),
-                        severity: Severity::High,
-                    });
+                    issues.push(Issue::new(
+                        &EC2_IMDSV2,
+                        address,
+                        "IMDSv2 (token-based) is not enforced, vulnerable to SSRF attacks".to_string(),
+                        Severity::High,
+                    ));
                 }
             }
         }
@@ -383,39 +816,44 @@ impl FastValidator {
     }
     
     /// Check S3 bucket configuration for security issues
-    pub fn check_s3_bucket(&self, address: String, resource: &Value) -> Vec<Issue> {
-        let mut issues = Vec::new();
+    pub fn check_s3_bucket(&self, context: &ResourceContext) -> Vec<Issue> {
+        let (address, resource) = (context.address.to_string(), context.values);
+        let mut issues = self.check_unknown(context, &["acl", "server_side_encryption_configuration", "versioning.*.enabled"]);
         
         // Check for public access configuration
         if let Some(acl) = resource.get("acl").and_then(|a| a.as_str()) {
             if acl == "public-read" || acl == "public-read-write" {
-                issues.push(Issue {
-                    resource: address.clone(),
-                    message: format!("S3 bucket has public access enabled (ACL: {})", acl),
-                    severity: Severity::High,
-                });
+                issues.push(Issue::new(
+                    &S3_PUBLIC_ACL,
+                    address.clone(),
+                    format!("S3 bucket has public access enabled (ACL: {})", acl),
+                    Severity::High,
+                )
+                .with_attribute("acl", acl));
             }
         }
         
         // Check for encryption
         let encryption_enabled = resource.get("server_side_encryption_configuration").is_some();
-        if !encryption_enabled {
-            issues.push(Issue {
-                resource: address.clone(),
-                message: "S3 bucket is missing server-side encryption".to_string(),
-                severity: Severity::Medium,
-            });
+        if !encryption_enabled && !context.is_unknown("server_side_encryption_configuration") {
+            issues.push(Issue::new(
+                &S3_NO_ENCRYPTION,
+                address.clone(),
+                "S3 bucket is missing server-side encryption".to_string(),
+                Severity::Medium,
+            ));
         }
         
         // Check for versioning
-        if let Some(versioning) = resource.get("versioning") {
+        if let Some(versioning) = block(resource, "versioning") {
             if let Some(enabled) = versioning.get("enabled") {
                 if !enabled.as_bool().unwrap_or(false) {
-                    issues.push(Issue {
-                        resource: address,
-                        message: "S3 bucket versioning is not enabled".to_string(),
-                        severity: Severity::Low,
-                    });
+                    issues.push(Issue::new(
+                        &S3_NO_VERSIONING,
+                        address,
+                        "S3 bucket versioning is not enabled".to_string(),
+                        Severity::Low,
+                    ));
                 }
             }
         }
@@ -424,9 +862,400 @@ impl FastValidator {
     }
 }
 
+/// Derive the resource type from an address such as
+/// `module.net.aws_security_group.web["prod"]` when the plan omits `type`
+fn type_from_address(address: &str) -> String {
+    match ResourceAddress::parse(address) {
+        Some(parsed) => parsed.resource_type,
+        None => address.to_string(),
+    }
+}
+
+/// Convert the flat `aws_vpc_security_group_*_rule` shape (single `cidr_ipv4`,
+/// `ip_protocol`) into an inline rule block
+fn vpc_rule_to_inline(resource: &Value) -> Value {
+    let single = |key: &str| match resource.get(key).and_then(|c| c.as_str()) {
+        Some(cidr) => serde_json::json!([cidr]),
+        None => serde_json::json!([]),
+    };
+    serde_json::json!({
+        "from_port": resource.get("from_port"),
+        "to_port": resource.get("to_port"),
+        "protocol": resource.get("ip_protocol"),
+        "cidr_blocks": single("cidr_ipv4"),
+        "ipv6_cidr_blocks": single("cidr_ipv6"),
+    })
+}
+
+/// A nested block, which plans render as a single-element list and
+/// hand-written inputs often as a plain object
+fn block<'v>(resource: &'v Value, key: &str) -> Option<&'v Value> {
+    match resource.get(key)? {
+        Value::Array(items) => items.first(),
+        other => Some(other),
+    }
+}
+
+/// CIDR strings listed in a rule's IPv4 and IPv6 blocks
+fn cidr_strings(rule: &Value) -> impl Iterator<Item = &str> {
+    ["cidr_blocks", "ipv6_cidr_blocks"].into_iter()
+        .filter_map(move |key| rule.get(key).and_then(|b| b.as_array()))
+        .flatten()
+        .filter_map(|c| c.as_str())
+}
+
+/// The rule's CIDR blocks that parse, together with their original spelling
+fn parsed_cidrs(rule: &Value) -> impl Iterator<Item = (&str, Cidr)> {
+    cidr_strings(rule).filter_map(|text| Cidr::parse(text).ok().map(|cidr| (text, cidr)))
+}
+
+impl Default for FastValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What checking one resource produced
+struct Checked {
+    address: String,
+    resource_type: String,
+    issues: Vec<Issue>,
+}
+
+impl Checked {
+    fn new(context: &ResourceContext, issues: Vec<Issue>) -> Self {
+        Self {
+            address: context.address.to_string(),
+            resource_type: context.resource_type.to_string(),
+            issues,
+        }
+    }
+}
+
 /// Fast validation implementation that uses the FastValidator struct
 pub fn fast_validate(plan: &Value) -> Vec<Issue> {
     let mut validator = FastValidator::new();
     validator.validate(plan)
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ChangeStatus;
+    use serde_json::json;
+
+    #[test]
+    fn test_dispatch_uses_resource_type() {
+        let plan = json!({
+            "resource_changes": [
+                {
+                    "address": "aws_lambda_function.aws_security_group",
+                    "type": "aws_lambda_function",
+                    "change": { "after": { "function_name": "aws_security_group" } }
+                },
+                {
+                    "address": "module.aws_security_group_wrapper.aws_s3_bucket.logs",
+                    "type": "aws_s3_bucket",
+                    "change": { "after": { "server_side_encryption_configuration": [{}] } }
+                }
+            ]
+        });
+
+        let mut validator = FastValidator::new();
+        let issues = validator.validate(&plan);
+        assert!(issues.is_empty());
+        assert_eq!(validator.checked_resources(), ["module.aws_security_group_wrapper.aws_s3_bucket.logs"]);
+        assert_eq!(validator.unknown_types().get("aws_lambda_function"), Some(&1));
+    }
+
+    #[test]
+    fn test_standalone_rules_resolve_parent_group() {
+        let plan = json!({
+            "resource_changes": [
+                {
+                    "address": "aws_security_group_rule.ssh",
+                    "type": "aws_security_group_rule",
+                    "change": { "after": {
+                        "type": "ingress", "from_port": 22, "to_port": 22, "protocol": "tcp",
+                        "cidr_blocks": ["0.0.0.0/0"]
+                    } }
+                },
+                {
+                    "address": "aws_vpc_security_group_ingress_rule.rdp",
+                    "type": "aws_vpc_security_group_ingress_rule",
+                    "change": { "after": {
+                        "from_port": 3389, "to_port": 3389, "ip_protocol": "tcp",
+                        "cidr_ipv4": "0.0.0.0/0", "security_group_id": "sg-123"
+                    } }
+                },
+                {
+                    "address": "aws_security_group.bastion",
+                    "type": "aws_security_group",
+                    "change": { "after": { "id": "sg-123", "tags": {} } }
+                }
+            ],
+            "configuration": { "root_module": { "resources": [{
+                "address": "aws_security_group_rule.ssh",
+                "expressions": { "security_group_id": {
+                    "references": ["aws_security_group.web.id", "aws_security_group.web"]
+                } }
+            }] } }
+        });
+
+        let issues = FastValidator::new().validate(&plan);
+        let ssh: Vec<&Issue> = issues.iter().filter(|i| i.resource == "aws_security_group_rule.ssh").collect();
+        assert!(ssh.iter().any(|i| i.rule_id == "TG-SG-001"));
+        assert!(ssh.iter().all(|i| i.attributes.get("security_group").map(String::as_str) == Some("aws_security_group.web")));
+
+        let rdp: Vec<&Issue> = issues.iter().filter(|i| i.resource == "aws_vpc_security_group_ingress_rule.rdp").collect();
+        assert!(rdp.iter().any(|i| i.rule_id == "TG-SG-003"));
+        assert!(rdp.iter().all(|i| i.message.contains("aws_security_group.bastion")));
+    }
+
+    #[test]
+    fn test_protocol_aware_ports() {
+        let group = json!({
+            "tags": {},
+            "ingress": [
+                { "protocol": "-1", "from_port": 0, "to_port": 0, "cidr_blocks": ["0.0.0.0/0"] },
+                { "protocol": "udp", "from_port": 22, "to_port": 22, "cidr_blocks": ["10.0.0.0/8"] },
+                { "protocol": "udp", "from_port": 53, "to_port": 53, "cidr_blocks": ["10.0.0.0/8"] },
+                { "protocol": "icmp", "from_port": -1, "to_port": -1, "cidr_blocks": ["10.0.0.0/8"] }
+            ]
+        });
+
+        let issues = FastValidator::new().check_ports("aws_security_group.sg".to_string(), &group);
+        let all = issues.iter().find(|i| i.rule_id == "TG-SG-010").expect("all-traffic finding");
+        assert_eq!(all.severity, Severity::High);
+        assert!(all.message.contains("tcp/22 (SSH)"));
+        assert!(!issues.iter().any(|i| i.message.contains("Port 0") || i.message.contains("-1")));
+        // UDP 22 is not SSH, but UDP 53 is DNS
+        assert!(issues.iter().any(|i| i.rule_id == "TG-SG-004" && i.message.contains("udp/22")));
+        assert!(issues.iter().any(|i| i.rule_id == "TG-SG-003" && i.message.contains("udp/53 (DNS)")));
+    }
+
+    #[test]
+    fn test_typed_plan_model() {
+        let plan = json!({
+            "format_version": "2.0",
+            "resource_changes": [
+                {
+                    "address": "data.aws_security_group.shared",
+                    "mode": "data",
+                    "type": "aws_security_group",
+                    "change": { "actions": ["read"], "after": { "ingress": [] } }
+                },
+                {
+                    "address": "module.net.aws_security_group_rule.ssh",
+                    "mode": "managed",
+                    "type": "aws_security_group_rule",
+                    "change": { "actions": ["create"], "after": {
+                        "type": "ingress", "from_port": 22, "to_port": 22, "protocol": "tcp",
+                        "cidr_blocks": ["0.0.0.0/0"]
+                    } }
+                }
+            ],
+            "configuration": { "root_module": { "module_calls": { "net": { "module": { "resources": [{
+                "address": "aws_security_group_rule.ssh",
+                "expressions": { "security_group_id": { "references": ["aws_security_group.web.id"] } }
+            }] } } } } }
+        });
+
+        let mut validator = FastValidator::new();
+        let issues = validator.validate(&plan);
+        assert_eq!(validator.checked_resources(), ["module.net.aws_security_group_rule.ssh"]);
+        assert!(issues.iter().all(|i| i.attributes.get("security_group").map(String::as_str) == Some("module.net.aws_security_group.web")));
+        assert!(validator.warnings()[0].contains("format_version 2.0"));
+
+        let mut validator = FastValidator::new();
+        assert!(validator.validate(&json!({ "resource_changes": {} })).is_empty());
+        assert!(validator.warnings()[0].contains("Terraform JSON plan format"));
+    }
+
+    #[test]
+    fn test_planned_values_child_modules() {
+        let open_group = json!({
+            "tags": { "Name": "sg" },
+            "ingress": [{ "protocol": "tcp", "from_port": 22, "to_port": 22, "cidr_blocks": ["0.0.0.0/0"] }]
+        });
+        let plan = json!({
+            "planned_values": { "root_module": {
+                "resources": [{ "address": "aws_s3_bucket.logs", "type": "aws_s3_bucket", "values": { "versioning": [{ "enabled": true }] } }],
+                "child_modules": [{
+                    "address": "module.network",
+                    "child_modules": [{
+                        "address": "module.network.module.sg",
+                        "resources": [{
+                            "address": "module.network.module.sg.aws_security_group.this",
+                            "type": "aws_security_group",
+                            "values": open_group
+                        }]
+                    }]
+                }]
+            } }
+        });
+
+        let issues = FastValidator::new().validate(&plan);
+        let nested: Vec<&Issue> = issues.iter().filter(|i| i.resource == "module.network.module.sg.aws_security_group.this").collect();
+        assert!(nested.iter().any(|i| i.rule_id == "TG-SG-003"));
+        assert!(nested.iter().all(|i| i.module.as_deref() == Some("module.network.module.sg")));
+        assert!(issues.iter().filter(|i| i.resource == "aws_s3_bucket.logs").all(|i| i.module.is_none()));
+    }
+
+    #[test]
+    fn test_action_aware_validation() {
+        let rule = |port: i64| json!({ "protocol": "tcp", "from_port": port, "to_port": port, "cidr_blocks": ["10.0.0.0/8"] });
+        let plan = json!({
+            "resource_changes": [
+                {
+                    "address": "aws_security_group.web",
+                    "type": "aws_security_group",
+                    "change": {
+                        "actions": ["update"],
+                        "before": { "tags": { "Name": "web" }, "ingress": [rule(23), rule(9000)] },
+                        "after": { "tags": { "Name": "web" }, "ingress": [rule(22), rule(9000)] }
+                    }
+                },
+                {
+                    "address": "aws_security_group.old",
+                    "type": "aws_security_group",
+                    "change": { "actions": ["delete"], "before": { "ingress": [rule(22)] }, "after": null }
+                }
+            ]
+        });
+
+        let issues = FastValidator::new().validate(&plan);
+        let status = |port: &str| issues.iter().find(|i| i.message.contains(port)).and_then(|i| i.change_status);
+        assert_eq!(status("tcp/22"), Some(ChangeStatus::Introduced));
+        assert_eq!(status("tcp/23"), Some(ChangeStatus::Fixed));
+        assert_eq!(status("tcp/9000"), Some(ChangeStatus::Kept));
+        assert!(issues.iter().all(|i| i.resource == "aws_security_group.web"));
+    }
+
+    #[test]
+    fn test_unknown_after_apply() {
+        let plan = json!({
+            "resource_changes": [
+                {
+                    "address": "aws_security_group.web",
+                    "type": "aws_security_group",
+                    "change": {
+                        "actions": ["create"],
+                        "after": { "tags": {}, "ingress": [
+                            { "protocol": "tcp", "from_port": 443, "to_port": 443 },
+                            { "protocol": "tcp", "cidr_blocks": ["10.0.0.0/8"] }
+                        ] },
+                        "after_unknown": { "ingress": [{ "cidr_blocks": true }, { "from_port": true, "to_port": true }] }
+                    }
+                },
+                {
+                    "address": "aws_s3_bucket.logs",
+                    "type": "aws_s3_bucket",
+                    "change": {
+                        "actions": ["create"],
+                        "after": { "versioning": [{ "enabled": true }] },
+                        "after_unknown": { "server_side_encryption_configuration": true }
+                    }
+                },
+                {
+                    "address": "aws_instance.web",
+                    "type": "aws_instance",
+                    "change": {
+                        "actions": ["create"],
+                        "after": { "metadata_options": [{}] },
+                        "after_unknown": { "metadata_options": [{ "http_tokens": true }] }
+                    }
+                }
+            ]
+        });
+
+        let issues = FastValidator::new().validate(&plan);
+        let unknown: Vec<&str> = issues.iter()
+            .filter(|i| i.rule_id == "TG-PLAN-001")
+            .map(|i| i.attributes["attribute"].as_str())
+            .collect();
+        assert_eq!(unknown, [
+            "ingress.0.cidr_blocks", "ingress.1.from_port", "ingress.1.to_port",
+            "server_side_encryption_configuration", "metadata_options.0.http_tokens",
+        ]);
+        assert!(issues[0].message.ends_with("until apply; it is computed from other resources"));
+        // Neither a 0-65535 range for the rule with unknown ports nor missing encryption
+        assert!(!issues.iter().any(|i| i.rule_id == "TG-SG-005" || i.rule_id == "TG-S3-002"));
+    }
+
+    #[test]
+    fn test_unknown_tags() {
+        let plan = json!({
+            "resource_changes": [{
+                "address": "aws_security_group.web",
+                "type": "aws_security_group",
+                "change": {
+                    "actions": ["create"],
+                    "after": { "ingress": [] },
+                    "after_unknown": { "tags": true }
+                }
+            }]
+        });
+
+        let issues = FastValidator::new().validate(&plan);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].rule_id, "TG-PLAN-001");
+        assert_eq!(issues[0].attributes["attribute"], "tags");
+    }
+
+    #[test]
+    fn test_raw_state_file() {
+        let state = json!({
+            "version": 4,
+            "resources": [{
+                "module": "module.network",
+                "mode": "managed",
+                "type": "aws_security_group",
+                "name": "web",
+                "instances": [
+                    { "index_key": "prod", "attributes": { "tags": {}, "ingress": [
+                        { "protocol": "tcp", "from_port": 22, "to_port": 22, "cidr_blocks": ["0.0.0.0/0"] }
+                    ] } },
+                    { "index_key": "prod", "deposed": "00000001", "attributes": {} }
+                ]
+            }]
+        });
+
+        let mut validator = FastValidator::new();
+        let issues = validator.validate(&state);
+        assert_eq!(validator.checked_resources(), ["module.network.aws_security_group.web[\"prod\"]"]);
+        assert!(issues.iter().any(|i| i.rule_id == "TG-SG-001" && i.index_key == Some(json!("prod"))));
+        assert!(state_resources(&json!({ "version": 3, "resources": [] })).is_err());
+    }
+
+    #[test]
+    fn test_parallel_validation_keeps_plan_order() {
+        let changes: Vec<Value> = (0..500).map(|i| json!({
+            "address": format!("aws_s3_bucket.b{}", i),
+            "type": if i % 7 == 0 { "aws_sqs_queue" } else { "aws_s3_bucket" },
+            "change": { "actions": ["create"], "after": { "acl": "public-read" } }
+        })).collect();
+        let plan = json!({ "resource_changes": changes });
+
+        let mut validator = FastValidator::new();
+        let issues = validator.validate(&plan);
+        let expected: Vec<String> = (0..500).filter(|i| i % 7 != 0).map(|i| format!("aws_s3_bucket.b{}", i)).collect();
+        assert_eq!(validator.checked_resources(), expected.as_slice());
+        assert_eq!(validator.unknown_types()["aws_sqs_queue"], 72);
+
+        let mut resources: Vec<&str> = issues.iter().map(|i| i.resource.as_str()).collect();
+        resources.dedup();
+        assert_eq!(resources, expected);
+        let fingerprints = |issues: &[Issue]| issues.iter().map(Issue::fingerprint).collect::<Vec<_>>();
+        assert_eq!(fingerprints(&issues), fingerprints(&FastValidator::new().validate(&plan)));
+    }
+
+    #[test]
+    fn test_type_from_address() {
+        assert_eq!(type_from_address("aws_instance.web"), "aws_instance");
+        assert_eq!(type_from_address("module.net.aws_security_group.sg[\"a.b\"]"), "aws_security_group");
+        assert_eq!(type_from_address("module.net[\"eu.west\"].module.sg.aws_security_group.this[0]"), "aws_security_group");
+        assert_eq!(type_from_address("data.aws_vpc.main"), "aws_vpc");
+    }
+}
@@ -0,0 +1,281 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use crate::catalog::UNUSED_SUPPRESSION;
+use crate::types::{Issue, Severity, SourceLocation};
+
+/// Default suppression file looked up in the working directory
+pub const DEFAULT_IGNORE_FILE: &str = ".terraguard-ignore";
+
+/// Justified, expiring exceptions loaded from a `.terraguard-ignore` file
+#[derive(Debug, Default, Deserialize)]
+pub struct Suppressions {
+    #[serde(default)]
+    pub suppressions: Vec<Suppression>,
+}
+
+/// A single exception for one rule on the resources matching a glob
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Suppression {
+    #[serde(alias = "rule_id")]
+    pub rule: String,
+    // Resource address glob; `*` matches any run of characters, `?` a single one
+    pub resource: String,
+    pub justification: String,
+    pub owner: String,
+    pub expires: NaiveDate,
+}
+
+/// An issue hidden by an active suppression, kept for the report
+#[derive(Debug, Clone, Serialize)]
+pub struct SuppressedIssue {
+    #[serde(flatten)]
+    pub issue: Issue,
+    pub status: &'static str,
+    pub suppression: Suppression,
+}
+
+/// An exception written on a resource block in Terraform source:
+/// `# terraguard:ignore=TG-SG-001 reason="Public load balancer"`
+#[derive(Debug, Clone, Serialize)]
+pub struct InlineSuppression {
+    pub rule: String,
+    // Address of the resource block the comment belongs to
+    pub resource: String,
+    pub reason: String,
+    pub location: SourceLocation,
+    // Findings the comment hides
+    pub suppressed: Vec<Issue>,
+}
+
+/// Hide the issues matched by inline suppressions. Returns the issues still
+/// reported, including one finding per comment that matched nothing, and the
+/// suppressions with the issues each of them hid.
+pub fn apply_inline(issues: Vec<Issue>, mut suppressions: Vec<InlineSuppression>) -> (Vec<Issue>, Vec<InlineSuppression>) {
+    let mut active = Vec::new();
+    for issue in issues {
+        match suppressions.iter_mut().find(|s| s.covers(&issue)) {
+            Some(suppression) => suppression.suppressed.push(issue),
+            None => active.push(issue),
+        }
+    }
+
+    // Comments that no longer hide anything would silently cover the next regression
+    for suppression in suppressions.iter().filter(|s| s.suppressed.is_empty()) {
+        active.push(Issue::new(
+            &UNUSED_SUPPRESSION,
+            suppression.resource.clone(),
+            format!("Suppression of {} matches no finding on this resource", suppression.rule),
+            Severity::Low,
+        )
+        .with_location(Some(suppression.location.clone()))
+        .with_attribute("rule", &suppression.rule));
+    }
+
+    (active, suppressions)
+}
+
+impl InlineSuppression {
+    /// Whether the comment hides `issue`. The same address may be declared in
+    /// several module directories, so the finding must also come from the
+    /// comment's file.
+    fn covers(&self, issue: &Issue) -> bool {
+        self.rule == issue.rule_id
+            && self.resource == issue.resource
+            && issue.location.as_ref().is_some_and(|location| location.file == self.location.file)
+    }
+}
+
+impl Suppression {
+    fn matches(&self, issue: &Issue) -> bool {
+        (self.rule == "*" || self.rule == issue.rule_id) && glob_match(&self.resource, &issue.resource)
+    }
+}
+
+impl Suppressions {
+    /// Load a suppression file, parsing `.json` files as JSON and anything else as YAML
+    pub fn load(path: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("could not read suppression file {}: {}", path, e))?;
+
+        let is_json = Path::new(path).extension().is_some_and(|ext| ext == "json");
+        let loaded: Suppressions = if is_json {
+            serde_json::from_str(&content).map_err(|e| format!("invalid JSON in suppression file {}: {}", path, e))?
+        } else {
+            serde_yaml::from_str(&content).map_err(|e| format!("invalid YAML in suppression file {}: {}", path, e))?
+        };
+
+        // An exception nobody can explain or own is not an exception
+        for entry in &loaded.suppressions {
+            if entry.justification.trim().is_empty() {
+                return Err(format!("suppression for {} on {} in {} has no justification", entry.rule, entry.resource, path));
+            }
+            if entry.owner.trim().is_empty() {
+                return Err(format!("suppression for {} on {} in {} has no owner", entry.rule, entry.resource, path));
+            }
+        }
+
+        Ok(loaded)
+    }
+
+    /// Load the explicitly given file, or `.terraguard-ignore` when it exists
+    pub fn discover(path: Option<&str>) -> Result<Self, String> {
+        match path {
+            Some(path) => Self::load(path),
+            None if Path::new(DEFAULT_IGNORE_FILE).is_file() => Self::load(DEFAULT_IGNORE_FILE),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Split issues into the ones still reported and the ones suppressed as of `today`.
+    /// Issues only covered by expired entries stay reported, with a note that the waiver lapsed.
+    pub fn apply(&self, issues: Vec<Issue>, today: NaiveDate) -> (Vec<Issue>, Vec<SuppressedIssue>) {
+        let mut active = Vec::new();
+        let mut suppressed = Vec::new();
+
+        for mut issue in issues {
+            let matching: Vec<&Suppression> = self.suppressions.iter().filter(|s| s.matches(&issue)).collect();
+
+            if let Some(entry) = matching.iter().find(|s| s.expires >= today) {
+                suppressed.push(SuppressedIssue {
+                    issue,
+                    status: "suppressed",
+                    suppression: (*entry).clone(),
+                });
+                continue;
+            }
+
+            if let Some(lapsed) = matching.iter().max_by_key(|s| s.expires) {
+                issue.message = format!("{} (waiver lapsed on {}, owner: {})", issue.message, lapsed.expires, lapsed.owner);
+            }
+            active.push(issue);
+        }
+
+        (active, suppressed)
+    }
+}
+
+/// Match a resource address against a glob supporting `*` and `?`. Other
+/// characters, including the brackets of indexed addresses, match literally.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::SG_OPEN_INGRESS_V4;
+    use crate::types::Severity;
+
+    fn issue(resource: &str) -> Issue {
+        Issue::new(&SG_OPEN_INGRESS_V4, resource.to_string(), "Open ingress".to_string(), Severity::High)
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("aws_security_group.*", "aws_security_group.public_api"));
+        assert!(glob_match("*.sg[\"prod\"]", "module.net.aws_security_group.sg[\"prod\"]"));
+        assert!(glob_match("aws_instance.web[?]", "aws_instance.web[3]"));
+        assert!(!glob_match("aws_security_group.*", "aws_instance.web"));
+        assert!(!glob_match("aws_instance.web[?]", "aws_instance.web[12]"));
+    }
+
+    #[test]
+    fn test_expired_waiver_is_reported_again() {
+        let suppressions: Suppressions = serde_yaml::from_str(r#"
+suppressions:
+  - rule: TG-SG-001
+    resource: "aws_security_group.public_*"
+    justification: "Public load balancer"
+    owner: "platform-team"
+    expires: 2026-06-30
+"#).unwrap();
+
+        let before = NaiveDate::from_ymd_opt(2026, 6, 30).unwrap();
+        let (active, suppressed) = suppressions.apply(vec![issue("aws_security_group.public_api"), issue("aws_security_group.db")], before);
+        assert_eq!(active.len(), 1);
+        assert_eq!(suppressed.len(), 1);
+        assert_eq!(suppressed[0].suppression.owner, "platform-team");
+
+        let after = NaiveDate::from_ymd_opt(2026, 7, 1).unwrap();
+        let (active, suppressed) = suppressions.apply(vec![issue("aws_security_group.public_api")], after);
+        assert!(suppressed.is_empty());
+        assert!(active[0].message.contains("waiver lapsed on 2026-06-30"));
+    }
+
+    #[test]
+    fn test_inline_suppressions() {
+        let inline = |rule: &str| InlineSuppression {
+            rule: rule.to_string(),
+            resource: "aws_security_group.public_api".to_string(),
+            reason: "Public load balancer".to_string(),
+            location: SourceLocation { file: "main.tf".to_string(), line: 3, column: 1 },
+            suppressed: Vec::new(),
+        };
+        let located = |resource: &str| issue(resource)
+            .with_location(Some(SourceLocation { file: "main.tf".to_string(), line: 4, column: 1 }));
+        let (active, inline) = apply_inline(
+            vec![located("aws_security_group.public_api"), located("aws_security_group.db")],
+            vec![inline("TG-SG-001"), inline("TG-SG-003")],
+        );
+
+        assert_eq!(inline[0].suppressed.len(), 1);
+        assert_eq!(active.len(), 2);
+        assert_eq!(active[0].resource, "aws_security_group.db");
+        assert_eq!(active[1].rule_id, "TG-IGNORE-001");
+        assert_eq!(active[1].location.as_ref().map(|l| l.line), Some(3));
+    }
+
+    #[test]
+    fn test_inline_suppressions_stay_in_their_file() {
+        let group = |comment: &str| format!(r#"
+{}
+resource "aws_security_group" "this" {{
+  tags = {{ Name = "this" }}
+  ingress {{
+    protocol    = "tcp"
+    from_port   = 443
+    to_port     = 443
+    cidr_blocks = ["0.0.0.0/0"]
+  }}
+}}
+"#, comment);
+        let mut sources = crate::hcl::parse_source(&group(r#"# terraguard:ignore=TG-SG-001 reason="Public load balancer""#), "modules/a/main.tf").unwrap();
+        sources.extend(crate::hcl::parse_source(&group(""), "modules/b/main.tf").unwrap());
+
+        let issues = crate::rules::FastValidator::new().validate_source(&sources);
+        let inline = sources.into_iter().flat_map(|source| source.suppressions).collect();
+        let (active, inline) = apply_inline(issues, inline);
+
+        assert_eq!(inline[0].suppressed.len(), 1);
+        assert_eq!(inline[0].suppressed[0].location.as_ref().unwrap().file, "modules/a/main.tf");
+        let open: Vec<&str> = active.iter()
+            .filter(|issue| issue.rule_id == "TG-SG-001")
+            .map(|issue| issue.location.as_ref().unwrap().file.as_str())
+            .collect();
+        assert_eq!(open, ["modules/b/main.tf"]);
+    }
+}
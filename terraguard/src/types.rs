@@ -1,35 +1,656 @@
 use serde::{Deserialize, Serialize};
+use crate::catalog::RuleInfo;
+use clap::ValueEnum;
 use colored::*;
+use std::collections::{BTreeMap, HashSet};
 
-#[derive(Debug, Deserialize)]
+/// Major versions of the `terraform show -json` plan format this model understands
+pub const SUPPORTED_FORMAT_MAJOR_VERSIONS: &[&str] = &["0", "1"];
+
+/// A plan in the `terraform show -json` format. Every field is optional so
+/// partial or hand-written plans still load.
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct Plan {
+    pub format_version: Option<String>,
+    pub terraform_version: Option<String>,
+    #[serde(default)]
+    pub variables: BTreeMap<String, PlanVariable>,
+    pub planned_values: Option<Values>,
+    pub prior_state: Option<State>,
+    pub configuration: Option<Configuration>,
+    // Changes made outside of Terraform since the last apply
+    #[serde(default)]
+    pub resource_drift: Vec<ResourceChange>,
+    #[serde(default)]
     pub resource_changes: Vec<ResourceChange>,
+    #[serde(default)]
+    pub output_changes: BTreeMap<String, Change>,
+}
+
+impl Plan {
+    /// A warning when the plan declares a format version this model does not support
+    pub fn format_warning(&self) -> Option<String> {
+        let version = self.format_version.as_deref()?;
+        let major = version.split('.').next().unwrap_or(version);
+        if SUPPORTED_FORMAT_MAJOR_VERSIONS.contains(&major) {
+            return None;
+        }
+        Some(format!(
+            "plan format_version {} is not supported (expected {}.x); results may be incomplete",
+            version,
+            SUPPORTED_FORMAT_MAJOR_VERSIONS.join(".x or "),
+        ))
+    }
+}
+
+/// An input variable value
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PlanVariable {
+    #[serde(default)]
+    pub value: serde_json::Value,
+}
+
+/// A snapshot of resource and output values, as in `planned_values` and `prior_state.values`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Values {
+    #[serde(default)]
+    pub outputs: BTreeMap<String, OutputValue>,
+    #[serde(default)]
+    pub root_module: Module,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OutputValue {
+    #[serde(default)]
+    pub sensitive: bool,
+    pub value: Option<serde_json::Value>,
+}
+
+/// A module in a values snapshot. The root module has no address.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Module {
+    pub address: Option<String>,
+    #[serde(default)]
+    pub resources: Vec<Resource>,
+    #[serde(default)]
+    pub child_modules: Vec<Module>,
+}
+
+impl Module {
+    /// Resources of this module and every nested child module, depth first
+    pub fn all_resources(&self) -> Vec<&Resource> {
+        let mut resources: Vec<&Resource> = self.resources.iter().collect();
+        for child in &self.child_modules {
+            resources.extend(child.all_resources());
+        }
+        resources
+    }
+}
+
+/// A resource instance in a values snapshot
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Resource {
+    pub address: String,
+    #[serde(default)]
+    pub mode: ResourceMode,
+    #[serde(rename = "type", default)]
+    pub resource_type: String,
+    #[serde(default)]
+    pub name: String,
+    // `count` index or `for_each` key
+    pub index: Option<serde_json::Value>,
+    pub provider_name: Option<String>,
+    pub schema_version: Option<u64>,
+    #[serde(default)]
+    pub values: serde_json::Value,
+    #[serde(default)]
+    pub sensitive_values: serde_json::Value,
+}
+
+/// A resource address split into its parts, e.g.
+/// `module.net["eu"].module.sg.aws_security_group.this[0]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceAddress {
+    // Module calls from the root down, each with its instance key: `module.net["eu"]`
+    pub modules: Vec<String>,
+    pub mode: ResourceMode,
+    pub resource_type: String,
+    pub name: String,
+    // Instance key including brackets: `[0]` or `["prod"]`
+    pub index: Option<String>,
+}
+
+impl ResourceAddress {
+    /// Parse an absolute resource address. Dots inside instance keys are
+    /// not treated as separators.
+    pub fn parse(address: &str) -> Option<Self> {
+        let mut segments = Vec::new();
+        let (mut start, mut in_brackets, mut in_quotes) = (0, false, false);
+        for (i, c) in address.char_indices() {
+            match c {
+                '"' if in_brackets => in_quotes = !in_quotes,
+                '[' if !in_quotes => in_brackets = true,
+                ']' if !in_quotes => in_brackets = false,
+                '.' if !in_brackets => {
+                    segments.push(&address[start..i]);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        segments.push(&address[start..]);
+
+        let mut modules = Vec::new();
+        let mut rest = segments.as_slice();
+        while let ["module", call, tail @ ..] = rest {
+            if tail.len() < 2 {
+                break;
+            }
+            modules.push(format!("module.{}", call));
+            rest = tail;
+        }
+
+        let (mode, resource_type, name) = match rest {
+            ["data", resource_type, name] => (ResourceMode::Data, resource_type, name),
+            [resource_type, name] => (ResourceMode::Managed, resource_type, name),
+            _ => return None,
+        };
+        let (name, index) = match name.find('[') {
+            Some(i) => (&name[..i], Some(name[i..].to_string())),
+            None => (*name, None),
+        };
+
+        Some(Self {
+            modules,
+            mode,
+            resource_type: resource_type.to_string(),
+            name: name.to_string(),
+            index,
+        })
+    }
+
+    /// Full path of the containing module, `None` for the root module
+    pub fn module_path(&self) -> Option<String> {
+        if self.modules.is_empty() {
+            None
+        } else {
+            Some(self.modules.join("."))
+        }
+    }
+
+    /// The `count` index or `for_each` key as it appears in the plan's `index`
+    pub fn index_key(&self) -> Option<serde_json::Value> {
+        let key = self.index.as_deref()?.strip_prefix('[')?.strip_suffix(']')?;
+        Some(serde_json::from_str(key).unwrap_or_else(|_| serde_json::Value::String(key.to_string())))
+    }
+
+    /// The address shared by every instance, with `[*]` in place of the index:
+    /// `aws_instance.worker[*]`
+    pub fn wildcard(&self) -> String {
+        self.join(self.modules.clone(), format!("{}[*]", self.name))
+    }
+
+    /// The address in the configuration, without any module or resource instance keys
+    pub fn config_address(&self) -> String {
+        let modules = self.modules.iter()
+            .map(|m| m.split('[').next().unwrap_or(m).to_string())
+            .collect();
+        self.join(modules, self.name.clone())
+    }
+
+    fn join(&self, mut parts: Vec<String>, name: String) -> String {
+        if self.mode == ResourceMode::Data {
+            parts.push("data".to_string());
+        }
+        parts.push(self.resource_type.clone());
+        parts.push(name);
+        parts.join(".")
+    }
+}
+
+/// Append the plan's `index` to an address that lacks it, so hand-trimmed
+/// plans listing every instance under one address keep them apart
+fn instance_address(address: &str, index: Option<&serde_json::Value>) -> String {
+    match index {
+        Some(index) if !address.ends_with(']') => format!("{}[{}]", address, index),
+        _ => address.to_string(),
+    }
+}
+
+/// Module path of a resource address such as `module.network.module.sg`,
+/// `None` for root module resources and addresses that are not Terraform's
+pub fn module_path(address: &str) -> Option<String> {
+    ResourceAddress::parse(address).and_then(|a| a.module_path())
+}
+
+impl Resource {
+    /// Address of the instance, including its index
+    pub fn instance_address(&self) -> String {
+        instance_address(&self.address, self.index.as_ref())
+    }
+}
+
+/// Prior state embedded in a plan, or `terraform show -json` output for a state
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct State {
+    pub format_version: Option<String>,
+    pub terraform_version: Option<String>,
+    pub values: Option<Values>,
+}
+
+/// Version of the raw `terraform.tfstate` format this model understands
+pub const SUPPORTED_STATE_VERSION: u64 = 4;
+
+/// A raw `terraform.tfstate` file
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StateFile {
+    pub version: u64,
+    pub terraform_version: Option<String>,
+    pub serial: Option<u64>,
+    pub lineage: Option<String>,
+    #[serde(default)]
+    pub resources: Vec<StateResource>,
+}
+
+/// A resource in a raw state file, with one entry per `count`/`for_each` instance
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StateResource {
+    // Module path such as `module.network.module.sg`; absent for the root module
+    pub module: Option<String>,
+    #[serde(default)]
+    pub mode: ResourceMode,
+    #[serde(rename = "type", default)]
+    pub resource_type: String,
+    #[serde(default)]
+    pub name: String,
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub instances: Vec<StateInstance>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StateInstance {
+    pub index_key: Option<serde_json::Value>,
+    pub schema_version: Option<u64>,
+    #[serde(default)]
+    pub attributes: serde_json::Value,
+    // Set for objects of create-before-destroy replacements awaiting deletion
+    pub deposed: Option<String>,
+    pub status: Option<String>,
+}
+
+impl StateFile {
+    /// Current resource instances with their full addresses; deposed objects are left out
+    pub fn instances(&self) -> Vec<Resource> {
+        let mut resources = Vec::new();
+        for resource in &self.resources {
+            let mut parts: Vec<&str> = resource.module.iter().map(String::as_str).collect();
+            if resource.mode == ResourceMode::Data {
+                parts.push("data");
+            }
+            parts.push(&resource.resource_type);
+            parts.push(&resource.name);
+            let address = parts.join(".");
+
+            for instance in resource.instances.iter().filter(|i| i.deposed.is_none()) {
+                resources.push(Resource {
+                    address: instance_address(&address, instance.index_key.as_ref()),
+                    mode: resource.mode,
+                    resource_type: resource.resource_type.clone(),
+                    name: resource.name.clone(),
+                    index: instance.index_key.clone(),
+                    provider_name: resource.provider.clone(),
+                    schema_version: instance.schema_version,
+                    values: instance.attributes.clone(),
+                    sensitive_values: serde_json::Value::Null,
+                });
+            }
+        }
+        resources
+    }
+}
+
+/// Whether a document is a state rather than a plan: a raw `terraform.tfstate`
+/// or the `terraform show -json` rendering of one
+pub fn is_state(document: &serde_json::Value) -> bool {
+    document.get("values").is_some() || document.get("resources").is_some_and(|r| r.is_array())
+}
+
+/// Resource instances of a state: a raw `terraform.tfstate` (version 4) or the
+/// `terraform show -json` rendering with `values.root_module`
+pub fn state_resources(state: &serde_json::Value) -> Result<Vec<Resource>, String> {
+    match state.get("version").and_then(|v| v.as_u64()) {
+        Some(SUPPORTED_STATE_VERSION) => StateFile::deserialize(state)
+            .map(|file| file.instances())
+            .map_err(|e| format!("invalid terraform.tfstate: {}", e)),
+        Some(version) => Err(format!("state version {} is not supported (expected {})", version, SUPPORTED_STATE_VERSION)),
+        None if state.get("resource_changes").is_some() || state.get("planned_values").is_some() => {
+            Err("input is a Terraform plan, not a state".to_string())
+        }
+        // `terraform show -json` omits `values` for an empty state
+        None if state.get("values").is_some() || state.get("format_version").is_some() => State::deserialize(state)
+            .map(|state| state.values.map(|v| v.root_module.all_resources().into_iter().cloned().collect()).unwrap_or_default())
+            .map_err(|e| format!("invalid state JSON: {}", e)),
+        None => Err("input is neither a terraform.tfstate file nor `terraform show -json` output for a state".to_string()),
+    }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResourceMode {
+    #[default]
+    Managed,
+    Data,
+}
+
+/// A planned (or drifted) change to one resource instance
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct ResourceChange {
+    #[serde(default)]
     pub address: String,
+    pub previous_address: Option<String>,
+    pub module_address: Option<String>,
+    #[serde(default)]
+    pub mode: ResourceMode,
+    #[serde(rename = "type", default)]
+    pub resource_type: String,
+    #[serde(default)]
+    pub name: String,
+    pub index: Option<serde_json::Value>,
+    pub provider_name: Option<String>,
+    // Set for changes to deposed objects of create-before-destroy replacements
+    pub deposed: Option<String>,
+    #[serde(default)]
     pub change: Change,
+    pub action_reason: Option<String>,
+}
+
+impl ResourceChange {
+    /// Address of the changed instance, including its index
+    pub fn instance_address(&self) -> String {
+        instance_address(&self.address, self.index.as_ref())
+    }
 }
 
-#[derive(Debug, Deserialize)]
+/// Before and after values of a change. `after_unknown` marks values only
+/// known after apply; the `*_sensitive` trees mark sensitive values.
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct Change {
+    #[serde(default)]
+    pub actions: Vec<Action>,
+    pub before: Option<serde_json::Value>,
     pub after: Option<serde_json::Value>,
+    pub after_unknown: Option<serde_json::Value>,
+    pub before_sensitive: Option<serde_json::Value>,
+    pub after_sensitive: Option<serde_json::Value>,
+    #[serde(default)]
+    pub replace_paths: Vec<serde_json::Value>,
+}
+
+impl Change {
+    /// Whether the object is removed without a replacement
+    pub fn is_delete(&self) -> bool {
+        !self.actions.is_empty() && self.actions.iter().all(|a| matches!(a, Action::Delete | Action::Forget))
+    }
+
+    /// Whether an existing object is updated in place or replaced
+    pub fn is_update(&self) -> bool {
+        self.actions.contains(&Action::Update)
+            || (self.actions.contains(&Action::Delete) && self.actions.contains(&Action::Create))
+    }
+
+    /// Mark the findings on the `after` values as introduced or kept by this
+    /// change. For updates, `check` is run on the `before` values and findings
+    /// that disappear are added with `ChangeStatus::Fixed`.
+    pub fn track_findings(&self, after: Vec<Issue>, check: impl Fn(&serde_json::Value) -> Vec<Issue>) -> Vec<Issue> {
+        let status = match self.actions.as_slice() {
+            [Action::Create] => Some(ChangeStatus::Introduced),
+            [Action::NoOp] | [Action::Read] => Some(ChangeStatus::Kept),
+            _ => None,
+        };
+        if !self.is_update() {
+            return after.into_iter().map(|issue| issue.with_change_status(status)).collect();
+        }
+
+        let before = match &self.before {
+            Some(before) if !before.is_null() => check(before),
+            _ => Vec::new(),
+        };
+        let before_prints: HashSet<String> = before.iter().map(Issue::fingerprint).collect();
+        let after_prints: HashSet<String> = after.iter().map(Issue::fingerprint).collect();
+
+        let mut tracked: Vec<Issue> = after.into_iter().map(|issue| {
+            let status = if before_prints.contains(&issue.fingerprint()) { ChangeStatus::Kept } else { ChangeStatus::Introduced };
+            issue.with_change_status(Some(status))
+        }).collect();
+        tracked.extend(before.into_iter()
+            .filter(|issue| !after_prints.contains(&issue.fingerprint()))
+            .map(|issue| issue.with_change_status(Some(ChangeStatus::Fixed))));
+        tracked
+    }
+}
+
+/// A single step of a planned change; replacements list both `delete` and `create`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Action {
+    NoOp,
+    Create,
+    Read,
+    Update,
+    Delete,
+    Forget,
+    // Actions added by newer Terraform versions
+    #[serde(other)]
+    Unknown,
+}
+
+/// The configuration section of a plan, used to resolve references
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Configuration {
+    #[serde(default)]
+    pub provider_config: BTreeMap<String, serde_json::Value>,
+    #[serde(default)]
+    pub root_module: ConfigModule,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ConfigModule {
+    #[serde(default)]
+    pub outputs: BTreeMap<String, serde_json::Value>,
+    #[serde(default)]
+    pub resources: Vec<ConfigResource>,
+    #[serde(default)]
+    pub module_calls: BTreeMap<String, ModuleCall>,
+    #[serde(default)]
+    pub variables: BTreeMap<String, serde_json::Value>,
+}
+
+/// A resource block in the configuration. Expressions keep their raw JSON
+/// shape since nested blocks are arbitrarily deep.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ConfigResource {
+    #[serde(default)]
+    pub address: String,
+    #[serde(default)]
+    pub mode: ResourceMode,
+    #[serde(rename = "type", default)]
+    pub resource_type: String,
+    #[serde(default)]
+    pub name: String,
+    pub provider_config_key: Option<String>,
+    #[serde(default)]
+    pub expressions: BTreeMap<String, serde_json::Value>,
+    pub schema_version: Option<u64>,
+    pub count_expression: Option<serde_json::Value>,
+    pub for_each_expression: Option<serde_json::Value>,
+}
+
+impl ConfigResource {
+    /// References made by a top-level attribute expression, e.g.
+    /// `["aws_security_group.web.id", "aws_security_group.web"]`
+    pub fn references(&self, attribute: &str) -> Vec<&str> {
+        self.expressions.get(attribute)
+            .and_then(|e| e.get("references"))
+            .and_then(|r| r.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|r| r.as_str())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ModuleCall {
+    #[serde(default)]
+    pub source: String,
+    #[serde(default)]
+    pub expressions: BTreeMap<String, serde_json::Value>,
+    pub count_expression: Option<serde_json::Value>,
+    pub for_each_expression: Option<serde_json::Value>,
+    #[serde(default)]
+    pub module: ConfigModule,
+    pub version_constraint: Option<String>,
+}
+
+/// How a planned change affects a finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeStatus {
+    // Only present after the change
+    Introduced,
+    // Present before and after: pre-existing debt
+    Kept,
+    // Only present before the change
+    Fixed,
 }
 
-#[derive(Debug, Clone, Eq, Hash, PartialEq, Deserialize, Serialize)]
+/// Where a finding's resource is declared in Terraform source, 1-based
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl std::fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+// Variants are declared from least to most severe so they can be compared
+#[derive(Debug, Clone, Eq, Hash, PartialEq, PartialOrd, Ord, Deserialize, Serialize, ValueEnum)]
 pub enum Severity {
+    #[serde(alias = "low", alias = "LOW")]
     Low,
+    #[serde(alias = "medium", alias = "MEDIUM")]
     Medium,
+    #[serde(alias = "high", alias = "HIGH")]
     High,
 }
 
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Issue {
+    // Stable identifier of the check that produced this issue (e.g. TG-SG-001)
+    #[serde(default)]
+    pub rule_id: String,
     pub resource: String,
+    // Full path of the module containing the resource; absent for the root module
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
+    // `count` index or `for_each` key of the resource instance
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index_key: Option<serde_json::Value>,
+    // Whether the planned change introduces, keeps or fixes the finding
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub change_status: Option<ChangeStatus>,
+    // Declaration of the resource when scanning `.tf` sources
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<SourceLocation>,
+    // Plan, state or template the finding comes from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
     pub message: String,
     pub severity: Severity,
+    // Short, message-independent summary of the rule
+    #[serde(default)]
+    pub title: String,
+    // How to fix the finding
+    #[serde(default)]
+    pub remediation: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc_url: Option<String>,
+    // Compliance controls covered by the rule (e.g. "CIS AWS 5.2")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub compliance: Vec<String>,
+    // Key attributes of the offending configuration (ports, CIDR, ...), used for fingerprinting
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, String>,
+}
+
+impl Issue {
+    /// Create an issue for a built-in rule, copying the rule's metadata
+    pub fn new(rule: &RuleInfo, resource: String, message: String, severity: Severity) -> Self {
+        Self {
+            rule_id: rule.id.to_string(),
+            module: module_path(&resource),
+            index_key: ResourceAddress::parse(&resource).and_then(|a| a.index_key()),
+            change_status: None,
+            location: None,
+            source: None,
+            resource,
+            message,
+            severity,
+            title: rule.title.to_string(),
+            remediation: rule.remediation.to_string(),
+            doc_url: rule.doc_url.map(str::to_string),
+            compliance: rule.compliance.iter().map(|c| c.to_string()).collect(),
+            attributes: BTreeMap::new(),
+        }
+    }
+
+    /// Set how the planned change affects the finding
+    pub fn with_change_status(mut self, status: Option<ChangeStatus>) -> Self {
+        self.change_status = status;
+        self
+    }
+
+    /// Set where the resource is declared
+    pub fn with_location(mut self, location: Option<SourceLocation>) -> Self {
+        self.location = location;
+        self
+    }
+
+    /// Set the input the finding comes from
+    pub fn with_source(mut self, source: &str) -> Self {
+        self.source = Some(source.to_string());
+        self
+    }
+
+    /// Record a key attribute of the finding
+    pub fn with_attribute(mut self, key: &str, value: impl ToString) -> Self {
+        self.attributes.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Stable fingerprint built from the rule, the resource address and the
+    /// key attributes. Message wording does not affect it.
+    pub fn fingerprint(&self) -> String {
+        let mut key = format!("{}|{}", self.rule_id, self.resource);
+        for (name, value) in &self.attributes {
+            key.push_str(&format!("|{}={}", name, value));
+        }
+
+        // FNV-1a keeps fingerprints identical across platforms and Rust versions
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in key.bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        format!("{:016x}", hash)
+    }
 }
 
 impl std::fmt::Display for Severity {
@@ -42,3 +663,50 @@ impl std::fmt::Display for Severity {
         write!(f, "{}", output)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fail_on_threshold() {
+        // `--fail-on` values parse case-insensitively
+        let threshold = Severity::from_str("medium", true).unwrap();
+        assert_eq!(threshold, Severity::Medium);
+        assert!(Severity::from_str("critical", true).is_err());
+
+        // A threshold is met by its own severity and everything above it
+        let met: Vec<bool> = [Severity::Low, Severity::Medium, Severity::High].iter()
+            .map(|severity| *severity >= threshold)
+            .collect();
+        assert_eq!(met, [false, true, true]);
+    }
+
+    #[test]
+    fn test_issue_carries_rule_metadata() {
+        use crate::catalog::{SG_MISSING_TAGS, SG_OPEN_INGRESS_V4};
+
+        let issue = Issue::new(&SG_OPEN_INGRESS_V4, "aws_security_group.web".to_string(), "Open".to_string(), Severity::High);
+        assert_eq!(issue.rule_id, "TG-SG-001");
+        assert_eq!(issue.title, SG_OPEN_INGRESS_V4.title);
+        assert_eq!(issue.remediation, SG_OPEN_INGRESS_V4.remediation);
+        assert_eq!(issue.doc_url.as_deref(), SG_OPEN_INGRESS_V4.doc_url);
+        assert_eq!(issue.compliance, ["CIS AWS 5.2", "AWS FSBP EC2.18"]);
+
+        let json = serde_json::to_value(&issue).unwrap();
+        assert_eq!(json["rule_id"], "TG-SG-001");
+        assert_eq!(json["compliance"][0], "CIS AWS 5.2");
+
+        // Optional metadata is left out rather than written as null or []
+        let mut bare = Issue::new(&SG_MISSING_TAGS, "aws_security_group.web".to_string(), "Untagged".to_string(), Severity::Medium);
+        bare.doc_url = None;
+        bare.compliance.clear();
+        let json = serde_json::to_value(&bare).unwrap();
+        assert!(json.get("doc_url").is_none());
+        assert!(json.get("compliance").is_none());
+
+        // Issues written before rule ids existed still load
+        let old: Issue = serde_json::from_str(r#"{"resource": "aws_s3_bucket.logs", "message": "Public", "severity": "High"}"#).unwrap();
+        assert_eq!(old.rule_id, "");
+    }
+}